@@ -1,18 +1,40 @@
 export type ServerMessage = ToxResponse | ToxEvent;
 
+export type ProfileRequest = ToxRequest & { "profile"?: string | null };
+export type ProfileMessage = ServerMessage & { "profile"?: string | null };
+
 export type ToxRequest =
+    Requests.Authenticate |
+    Requests.ListProfiles |
+    Requests.CreateProfile |
+    Requests.LoadProfile |
+    Requests.UnloadProfile |
     Requests.Info |
     Requests.SetInfo |
     Requests.AddFriend |
     Requests.AddFriendNorequest |
     Requests.SendFriendMessage |
     Requests.DeleteFriend |
+    Requests.ListFriendRequests |
+    Requests.AcceptFriendRequest |
+    Requests.RejectFriendRequest |
+    Requests.Block |
+    Requests.Unblock |
+    Requests.GetBlockList |
 
     Requests.GetConnectionStatus |
+    Requests.GetDhtId |
+    Requests.GetUdpPort |
+    Requests.GetTcpPort |
+    Requests.GetNodeStatus |
+    Requests.GetOptions |
+    Requests.RestartWithOptions |
     Requests.GetAddress |
     Requests.GetNospam |
     Requests.SetNospam |
     Requests.GetPublicKey |
+    Requests.GetSecretKey |
+    Requests.ExportSecretKey |
     Requests.SetName |
     Requests.GetName |
     Requests.SetStatusMessage |
@@ -27,14 +49,23 @@ export type ToxRequest =
     Requests.GetFriendStatusMessage |
     Requests.GetFriendStatus |
     Requests.GetFriendConnectionStatus |
+    Requests.GetFriendList |
+    Requests.GetFriend |
+    Requests.GetFriendMetadata |
+    Requests.SetFriendMetadata |
 
     Requests.ControlFile |
     Requests.SeekFile |
     Requests.GetFileId |
     Requests.SendFile |
     Requests.SendFileChunk |
+    Requests.GetFileTransfers |
+    Requests.GetPartialFiles |
+    Requests.ForgetPartialFile |
 
     Requests.SendAvatar |
+    Requests.SetAvatar |
+    Requests.GetAvatar |
 
     Requests.NewConference |
     Requests.DeleteConference |
@@ -42,6 +73,11 @@ export type ToxRequest =
     Requests.ConferencePeerCount |
     Requests.GetPeerName |
     Requests.GetPeerPublicKey |
+    Requests.GetOfflinePeerList |
+    Requests.ConferenceOfflinePeerCount |
+    Requests.GetOfflinePeerName |
+    Requests.GetOfflinePeerPublicKey |
+    Requests.GetOfflinePeerLastActive |
     Requests.IsOwnPeerNumber |
     Requests.InviteToConference |
     Requests.JoinConference |
@@ -49,53 +85,132 @@ export type ToxRequest =
     Requests.GetConferenceTitle |
     Requests.SetConferenceTitle |
     Requests.GetConferenceList |
-    Requests.GetConferenceType;
+    Requests.GetConferenceType |
+    Requests.GetConferenceId |
+    Requests.GetConferenceById |
+
+    Requests.NewGroup |
+    Requests.JoinGroup |
+    Requests.AcceptGroupInvite |
+    Requests.InviteToGroup |
+    Requests.LeaveGroup |
+    Requests.GetGroupList |
+    Requests.GetGroupChatId |
+    Requests.GetGroupPeerList |
+    Requests.GetGroupTopic |
+    Requests.SetGroupTopic |
+    Requests.SendGroupMessage |
+    Requests.SendGroupPrivateMessage |
+    Requests.SetGroupRole |
+    Requests.KickGroupPeer |
+    Requests.BanGroupPeer |
+    Requests.SetGroupPassword |
+    Requests.SetGroupPrivacyState |
+
+    Requests.Call |
+    Requests.Answer |
+    Requests.CallControl |
+    Requests.SetAudioBitRate |
+    Requests.SetVideoBitRate |
+    Requests.SendAudioFrame |
+    Requests.SendVideoFrame;
 
 export type ToxResponse =
     Responses.Ok |
+    Responses.Unauthenticated |
+    Responses.AuthenticationFailed |
+    Responses.PermissionDenied |
+    Responses.ValidationError |
+    Responses.RateLimited |
+    Responses.Profiles |
+    Responses.Profile |
+    Responses.ProfileError |
     Responses.Info |
     Responses.MessageSent |
 
     Responses.ConnectionStatus |
+    Responses.DhtId |
+    Responses.Port |
+    Responses.NodeStatus |
+    Responses.Options |
     Responses.Address |
     Responses.Nospam |
     Responses.PublicKey |
+    Responses.SecretKey |
+    Responses.EncryptedSecretKey |
     Responses.Name |
     Responses.StatusMessage |
     Responses.Status |
     Responses.Friend |
     Responses.FriendExists |
     Responses.LastOnline |
+    Responses.FriendList |
+    Responses.FriendRecord |
+    Responses.RosterSync |
+    Responses.FriendMetadata |
+    Responses.FriendRequests |
+    Responses.BlockList |
 
     Responses.FileId |
     Responses.FileNumber |
+    Responses.FileTransfers |
+    Responses.PartialFiles |
     Responses.FileControlError |
     Responses.FileSeekError |
     Responses.FileGetError |
     Responses.FileSendError |
     Responses.FileSendChunkError |
 
+    Responses.Avatar |
+    Responses.AvatarNotFoundError |
+
     Responses.Conference |
     Responses.ConferencePeerList |
     Responses.ConferencePeerCount |
     Responses.ConferencePeerName |
     Responses.ConferencePeerPublicKey |
+    Responses.ConferencePeerLastActive |
     Responses.IsOwnPeerNumber |
     Responses.ConferenceTitle |
     Responses.ConferenceList |
     Responses.ConferenceType |
+    Responses.ConferenceId |
+
+    Responses.Group |
+    Responses.GroupChatId |
+    Responses.GroupList |
+    Responses.GroupPeerList |
+    Responses.GroupTopic |
 
     Responses.AddFriendError |
     Responses.SendFriendMessageError |
     Responses.FriendNotFoundError |
+    Responses.PortError |
+    Responses.RestartError |
+    Responses.FriendRequestNotFoundError |
     Responses.ConferenceInviteError |
     Responses.ConferenceJoinError |
     Responses.ConferencePeerQueryError |
     Responses.ConferenceSendError |
-    Responses.ConferenceTitleError;
+    Responses.ConferenceTitleError |
+    Responses.ConferenceNotFoundError |
+    Responses.GroupNewError |
+    Responses.GroupJoinError |
+    Responses.GroupInviteError |
+    Responses.GroupLeaveError |
+    Responses.GroupQueryError |
+    Responses.GroupTopicError |
+    Responses.GroupSendError |
+    Responses.GroupModerationError |
+    Responses.GroupFounderError |
+    Responses.CallError |
+    Responses.AnswerError |
+    Responses.CallControlError |
+    Responses.BitRateSetError |
+    Responses.SendFrameError;
 
 export type ToxEvent =
-    Events.SecretKey |
+    Events.SecretKeyDisclosed |
     Events.ConnectionStatus |
     Events.FriendRequest |
     Events.FriendMessage |
@@ -105,23 +220,108 @@ export type ToxEvent =
     Events.FriendConnectionStatus |
     Events.FriendTyping |
     Events.FriendReadReceipt |
+    Events.FriendRequestsChanged |
     Events.FileControlReceipt |
     Events.FileChunkRequest |
     Events.FileReceipt |
     Events.FileChunkReceipt |
+    Events.FileTransferProgress |
+    Events.FileResumed |
+    Events.FriendAvatarChanged |
     Events.ConferenceInvite |
     Events.ConferenceConnected |
     Events.ConferenceMessage |
     Events.ConferenceTitle |
     Events.ConferencePeerName |
-    Events.ConferencePeerListChanged;
-
+    Events.ConferencePeerListChanged |
+    Events.ConferencePeerOffline |
+    Events.ConferencePeerJoined |
+    Events.ConferencePeerLeft |
+    Events.GroupInvite |
+    Events.GroupSelfJoin |
+    Events.GroupJoinFail |
+    Events.GroupPeerJoin |
+    Events.GroupPeerExit |
+    Events.GroupPeerName |
+    Events.GroupTopic |
+    Events.GroupMessage |
+    Events.GroupPrivateMessage |
+    Events.GroupModeration |
+    Events.GroupPrivacyState |
+    Events.GroupPassword |
+    Events.CallIncoming |
+    Events.CallState |
+    Events.AudioBitRateSuggestion |
+    Events.VideoBitRateSuggestion |
+    Events.AudioFrameReceipt |
+    Events.VideoFrameReceipt;
+
+export type Scope = "Read" | "Message" | "File" | "Admin" | "Secrets";
 export type ConnectionStatus = "None" | "Tcp" | "Udp";
+export type ProxyType = "Http" | "Socks5";
 export type UserStatus = "None" | "Away" | "Busy";
 export type MessageType = "Normal" | "Action";
 export type ConferenceType = "Text" | "Av";
 export type FileControl = "Resume" | "Pause" | "Cancel";
-export type FileKind = "Data" | "Avatar";
+export type FileKind = "Data" | "Avatar" | { "Unknown": number };
+export type ToxCallControl =
+    "Resume" |
+    "Pause" |
+    "Cancel" |
+    "MuteAudio" |
+    "UnmuteAudio" |
+    "HideVideo" |
+    "ShowVideo";
+export type GroupRole = "Founder" | "Moderator" | "User" | "Observer";
+export type ToxGroupPrivacyState = "Public" | "Private";
+export type GroupModerationAction = "Kick" | "Ban" | "Observer" | "User" | "Moderator";
+export type GroupJoinFailReason = "PeerLimit" | "InvalidPassword" | "Unknown";
+export type FileDirection = "Incoming" | "Outgoing";
+export type FileTransferState = "Running" | "Paused" | "Cancelled";
+
+export interface NodeStatusInfo {
+    "connection": ConnectionStatus,
+    "dht_nodes": number,
+    "tcp_relays": number,
+}
+
+export interface ToxOptions {
+    "ipv6_enabled": boolean,
+    "udp_enabled": boolean,
+    "local_discovery_enabled": boolean,
+    "hole_punching_enabled": boolean,
+    "proxy": Proxy | null,
+    "start_port": number,
+    "end_port": number,
+    "tcp_port": number,
+}
+
+export interface Proxy {
+    "kind": ProxyType,
+    "host": string,
+    "port": number,
+}
+
+export interface ProfileInfo {
+    "id": string,
+    "name": string,
+    "public_key": string,
+    "loaded": boolean,
+}
+
+export type RosterOutcome =
+    "Unchanged" |
+    { "Added": { "friend": number } } |
+    "Removed" |
+    { "AddFailed": { "error": Errors.AddFriendError } } |
+    "RemoveFailed" |
+    "InvalidKey" |
+    "NotApplied";
+
+export interface RosterResult {
+    "public_key": string,
+    "outcome": RosterOutcome,
+}
 
 export interface FriendInfo {
     "number": number,
@@ -130,26 +330,115 @@ export interface FriendInfo {
     "status": UserStatus,
     "status_message": string,
     "last_online": number,
+    "connection_status": ConnectionStatus,
+    "is_typing": boolean,
+    "metadata": LocalMetadata,
+    "blocked": boolean,
+}
+
+export interface LocalMetadata {
+    "alias": string | null,
+    "notes": string,
+    "tags": string[],
+}
+
+export interface PendingFriendRequest {
+    "public_key": string,
+    "message": string,
+    "received": number,
 }
 
 export interface PeerInfo {
     "number": number,
     "public_key": string,
     "name": string,
+    "online": boolean,
 }
 
 export interface ConferenceInfo {
     "number": number,
+    "id": string,
     "kind": ConferenceType,
     "title": string,
     "peers": PeerInfo[]
 }
 
+export interface GroupPeerInfo {
+    "number": number,
+    "public_key": string,
+    "name": string,
+    "role": GroupRole,
+}
+
+export interface GroupInfo {
+    "number": number,
+    "chat_id": string,
+    "name": string,
+    "topic": string,
+    "privacy": ToxGroupPrivacyState,
+    "role": GroupRole,
+    "peers": GroupPeerInfo[],
+}
+
+export interface FileTransfer {
+    "friend": number,
+    "file_number": number,
+    "direction": FileDirection,
+    "kind": FileKind,
+    "state": FileTransferState,
+    "file_name": string,
+    "transferred": number,
+    "total": number,
+}
+
+export interface PartialFile {
+    "file_id": string,
+    "kind": FileKind,
+    "file_name": string,
+    "file_size": number,
+    "position": number,
+}
+
+export interface CallStateInfo {
+    "error": boolean,
+    "finished": boolean,
+    "sending_audio": boolean,
+    "sending_video": boolean,
+    "accepting_audio": boolean,
+    "accepting_video": boolean,
+}
+
 export namespace Requests {
     export interface Request {
         "request": string,
     }
 
+    export interface Authenticate extends Request {
+        "request": "Authenticate",
+        "token": string,
+    }
+
+    export interface ListProfiles extends Request {
+        "request": "ListProfiles",
+    }
+
+    export interface CreateProfile extends Request {
+        "request": "CreateProfile",
+        "name": string,
+        "password": string | null,
+    }
+
+    export interface LoadProfile extends Request {
+        "request": "LoadProfile",
+        "id": string,
+        "password": string | null,
+    }
+
+    export interface UnloadProfile extends Request {
+        "request": "UnloadProfile",
+        "id": string,
+    }
+
     export interface Info extends Request {
         "request": "Info",
     }
@@ -160,13 +449,38 @@ export namespace Requests {
         "name": string
         "status": UserStatus
         "status_message": string
-        "friends": string[]
+        "friends"?: string[] | null
     }
 
     interface GetConnectionStatus extends Request {
         "request": "GetConnectionStatus"
     }
 
+    interface GetDhtId extends Request {
+        "request": "GetDhtId"
+    }
+
+    interface GetUdpPort extends Request {
+        "request": "GetUdpPort"
+    }
+
+    interface GetTcpPort extends Request {
+        "request": "GetTcpPort"
+    }
+
+    interface GetNodeStatus extends Request {
+        "request": "GetNodeStatus"
+    }
+
+    interface GetOptions extends Request {
+        "request": "GetOptions"
+    }
+
+    interface RestartWithOptions extends Request {
+        "request": "RestartWithOptions"
+        "options": ToxOptions
+    }
+
     interface GetAddress extends Request {
         "request": "GetAddress"
     }
@@ -184,6 +498,15 @@ export namespace Requests {
         "request": "GetPublicKey"
     }
 
+    interface GetSecretKey extends Request {
+        "request": "GetSecretKey"
+    }
+
+    interface ExportSecretKey extends Request {
+        "request": "ExportSecretKey"
+        "passphrase": string
+    }
+
     interface SetName extends Request {
         "request": "SetName"
         "name": string
@@ -251,6 +574,26 @@ export namespace Requests {
         "friend": number
     }
 
+    interface GetFriendList extends Request {
+        "request": "GetFriendList"
+    }
+
+    interface GetFriend extends Request {
+        "request": "GetFriend"
+        "friend": number
+    }
+
+    interface GetFriendMetadata extends Request {
+        "request": "GetFriendMetadata"
+        "public_key": string
+    }
+
+    interface SetFriendMetadata extends Request {
+        "request": "SetFriendMetadata"
+        "public_key": string
+        "metadata": Partial<LocalMetadata>
+    }
+
     export interface AddFriend extends Request {
         "request": "AddFriend",
         "tox_id": string,
@@ -274,6 +617,34 @@ export namespace Requests {
         "friend": number
     }
 
+    export interface ListFriendRequests extends Request {
+        "request": "ListFriendRequests"
+    }
+
+    export interface AcceptFriendRequest extends Request {
+        "request": "AcceptFriendRequest"
+        "public_key": string
+    }
+
+    export interface RejectFriendRequest extends Request {
+        "request": "RejectFriendRequest"
+        "public_key": string
+    }
+
+    export interface Block extends Request {
+        "request": "Block"
+        "public_key": string
+    }
+
+    export interface Unblock extends Request {
+        "request": "Unblock"
+        "public_key": string
+    }
+
+    export interface GetBlockList extends Request {
+        "request": "GetBlockList"
+    }
+
     export interface ControlFile extends Request {
         "request": "ControlFile"
         "friend": number
@@ -310,6 +681,19 @@ export namespace Requests {
         "data": string
     }
 
+    export interface GetFileTransfers extends Request {
+        "request": "GetFileTransfers"
+    }
+
+    export interface GetPartialFiles extends Request {
+        "request": "GetPartialFiles"
+    }
+
+    export interface ForgetPartialFile extends Request {
+        "request": "ForgetPartialFile"
+        "file_id": string
+    }
+
     export interface SendAvatar extends Request {
         "request": "SendAvatar"
         "friend": number
@@ -317,6 +701,16 @@ export namespace Requests {
         "file_hash": string
     }
 
+    export interface SetAvatar extends Request {
+        "request": "SetAvatar"
+        "data": string
+    }
+
+    export interface GetAvatar extends Request {
+        "request": "GetAvatar"
+        "friend": number
+    }
+
     export interface NewConference extends Request {
         "request": "NewConference",
     }
@@ -348,6 +742,34 @@ export namespace Requests {
         "peer": number,
     }
 
+    export interface GetOfflinePeerList extends Request {
+        "request": "GetOfflinePeerList"
+        "conference": number
+    }
+
+    export interface ConferenceOfflinePeerCount extends Request {
+        "request": "ConferenceOfflinePeerCount",
+        "conference": number,
+    }
+
+    export interface GetOfflinePeerName extends Request {
+        "request": "GetOfflinePeerName",
+        "conference": number,
+        "peer": number,
+    }
+
+    export interface GetOfflinePeerPublicKey extends Request {
+        "request": "GetOfflinePeerPublicKey",
+        "conference": number,
+        "peer": number,
+    }
+
+    export interface GetOfflinePeerLastActive extends Request {
+        "request": "GetOfflinePeerLastActive",
+        "conference": number,
+        "peer": number,
+    }
+
     export interface IsOwnPeerNumber extends Request {
         "request": "IsOwnPeerNumber",
         "conference": number,
@@ -392,6 +814,172 @@ export namespace Requests {
         "request": "GetConferenceType",
         "conference": number,
     }
+
+    export interface GetConferenceId extends Request {
+        "request": "GetConferenceId",
+        "conference": number,
+    }
+
+    export interface GetConferenceById extends Request {
+        "request": "GetConferenceById",
+        "id": string,
+    }
+
+    export interface Call extends Request {
+        "request": "Call",
+        "friend": number,
+        "audio_bit_rate": number,
+        "video_bit_rate": number,
+    }
+
+    export interface Answer extends Request {
+        "request": "Answer",
+        "friend": number,
+        "audio_bit_rate": number,
+        "video_bit_rate": number,
+    }
+
+    export interface CallControl extends Request {
+        "request": "CallControl",
+        "friend": number,
+        "control": ToxCallControl,
+    }
+
+    export interface SetAudioBitRate extends Request {
+        "request": "SetAudioBitRate",
+        "friend": number,
+        "bit_rate": number,
+    }
+
+    export interface SetVideoBitRate extends Request {
+        "request": "SetVideoBitRate",
+        "friend": number,
+        "bit_rate": number,
+    }
+
+    export interface SendAudioFrame extends Request {
+        "request": "SendAudioFrame",
+        "friend": number,
+        /** Base64 of interleaved signed 16-bit little-endian samples */
+        "pcm": string,
+        "channels": number,
+        "sample_rate": number,
+    }
+
+    export interface SendVideoFrame extends Request {
+        "request": "SendVideoFrame",
+        "friend": number,
+        "width": number,
+        "height": number,
+        "y": string,
+        "u": string,
+        "v": string,
+    }
+
+    export interface NewGroup extends Request {
+        "request": "NewGroup",
+        "privacy": ToxGroupPrivacyState,
+        "name": string,
+        "self_name": string,
+    }
+
+    export interface JoinGroup extends Request {
+        "request": "JoinGroup",
+        "chat_id": string,
+        "self_name": string,
+        "password": string | null,
+    }
+
+    export interface AcceptGroupInvite extends Request {
+        "request": "AcceptGroupInvite",
+        "friend": number,
+        "invite_data": string,
+        "self_name": string,
+        "password": string | null,
+    }
+
+    export interface InviteToGroup extends Request {
+        "request": "InviteToGroup",
+        "friend": number,
+        "group": number,
+    }
+
+    export interface LeaveGroup extends Request {
+        "request": "LeaveGroup",
+        "group": number,
+        "message": string,
+    }
+
+    export interface GetGroupList extends Request {
+        "request": "GetGroupList",
+    }
+
+    export interface GetGroupChatId extends Request {
+        "request": "GetGroupChatId",
+        "group": number,
+    }
+
+    export interface GetGroupPeerList extends Request {
+        "request": "GetGroupPeerList",
+        "group": number,
+    }
+
+    export interface GetGroupTopic extends Request {
+        "request": "GetGroupTopic",
+        "group": number,
+    }
+
+    export interface SetGroupTopic extends Request {
+        "request": "SetGroupTopic",
+        "group": number,
+        "topic": string,
+    }
+
+    export interface SendGroupMessage extends Request {
+        "request": "SendGroupMessage",
+        "group": number,
+        "kind": MessageType,
+        "message": string,
+    }
+
+    export interface SendGroupPrivateMessage extends Request {
+        "request": "SendGroupPrivateMessage",
+        "group": number,
+        "peer": number,
+        "kind": MessageType,
+        "message": string,
+    }
+
+    export interface SetGroupRole extends Request {
+        "request": "SetGroupRole",
+        "group": number,
+        "peer": number,
+        "role": GroupRole,
+    }
+
+    export interface KickGroupPeer extends Request {
+        "request": "KickGroupPeer",
+        "group": number,
+        "peer": number,
+    }
+
+    export interface BanGroupPeer extends Request {
+        "request": "BanGroupPeer",
+        "group": number,
+        "peer": number,
+    }
+
+    export interface SetGroupPassword extends Request {
+        "request": "SetGroupPassword",
+        "group": number,
+        "password": string | null,
+    }
+
+    export interface SetGroupPrivacyState extends Request {
+        "request": "SetGroupPrivacyState",
+        "group": number,
+        "privacy": ToxGroupPrivacyState,
+    }
 }
 
 export namespace Responses {
@@ -403,6 +991,45 @@ export namespace Responses {
         "response": "Ok"
     }
 
+    export interface Unauthenticated extends Response {
+        "response": "Unauthenticated"
+    }
+
+    export interface AuthenticationFailed extends Response {
+        "response": "AuthenticationFailed"
+    }
+
+    export interface PermissionDenied extends Response {
+        "response": "PermissionDenied"
+        "scope": Scope
+    }
+
+    export interface ValidationError extends Response {
+        "response": "ValidationError"
+        "field": string
+        "error": Errors.ValidationError
+    }
+
+    export interface RateLimited extends Response {
+        "response": "RateLimited"
+        "retry_after_ms": number
+    }
+
+    export interface Profiles extends Response {
+        "response": "Profiles"
+        "profiles": ProfileInfo[]
+    }
+
+    export interface Profile extends Response {
+        "response": "Profile"
+        "id": string
+    }
+
+    export interface ProfileError extends Response {
+        "response": "ProfileError"
+        "error": Errors.ProfileError
+    }
+
     export interface MessageSent extends Response {
         "response": "MessageSent",
         "message_id": number
@@ -422,6 +1049,26 @@ export namespace Responses {
         "status": ConnectionStatus
     }
 
+    export interface DhtId extends Response {
+        "response": "DhtId"
+        "dht_id": string
+    }
+
+    export interface Port extends Response {
+        "response": "Port"
+        "port": number
+    }
+
+    export interface NodeStatus extends Response {
+        "response": "NodeStatus"
+        "status": NodeStatusInfo
+    }
+
+    export interface Options extends Response {
+        "response": "Options"
+        "options": ToxOptions
+    }
+
     export interface Address extends Response {
         "response": "Address"
         "address": string
@@ -437,6 +1084,16 @@ export namespace Responses {
         "public_key": string
     }
 
+    export interface SecretKey extends Response {
+        "response": "SecretKey"
+        "secret_key": string
+    }
+
+    export interface EncryptedSecretKey extends Response {
+        "response": "EncryptedSecretKey"
+        "data": string
+    }
+
     export interface Name extends Response {
         "response": "Name"
         "name": string
@@ -467,6 +1124,36 @@ export namespace Responses {
         "last_online": number
     }
 
+    export interface FriendList extends Response {
+        "response": "FriendList"
+        "friends": FriendInfo[]
+    }
+
+    export interface FriendRecord extends Response {
+        "response": "FriendRecord"
+        "friend": FriendInfo
+    }
+
+    export interface RosterSync extends Response {
+        "response": "RosterSync"
+        "results": RosterResult[]
+    }
+
+    export interface FriendMetadata extends Response {
+        "response": "FriendMetadata"
+        "metadata": LocalMetadata
+    }
+
+    export interface FriendRequests extends Response {
+        "response": "FriendRequests"
+        "requests": PendingFriendRequest[]
+    }
+
+    export interface BlockList extends Response {
+        "response": "BlockList"
+        "public_keys": string[]
+    }
+
     export interface FileId extends Response {
         "response": "FileId"
         "id": string
@@ -477,6 +1164,16 @@ export namespace Responses {
         "file_number": number
     }
 
+    export interface FileTransfers extends Response {
+        "response": "FileTransfers"
+        "transfers": FileTransfer[]
+    }
+
+    export interface PartialFiles extends Response {
+        "response": "PartialFiles"
+        "files": PartialFile[]
+    }
+
     export interface FileControlError extends Response {
         "response": "FileControlError"
         "error": FileControlError
@@ -502,6 +1199,16 @@ export namespace Responses {
         "error": FileSendChunkError
     }
 
+    export interface Avatar extends Response {
+        "response": "Avatar"
+        "hash": string
+        "data": string
+    }
+
+    export interface AvatarNotFoundError extends Response {
+        "response": "AvatarNotFoundError"
+    }
+
     export interface Conference extends Response {
         "response": "Conference",
         "conference": number
@@ -527,6 +1234,11 @@ export namespace Responses {
         "public_key": string,
     }
 
+    export interface ConferencePeerLastActive extends Response {
+        "response": "ConferencePeerLastActive",
+        "last_active": number,
+    }
+
     export interface IsOwnPeerNumber extends Response {
         "response": "IsOwnPeerNumber",
         "is_own": boolean,
@@ -547,6 +1259,11 @@ export namespace Responses {
         "kind": ConferenceType
     }
 
+    export interface ConferenceId extends Response {
+        "response": "ConferenceId",
+        "id": string
+    }
+
     export interface AddFriendError extends Response {
         "response": "AddFriendError",
         "error": Errors.AddFriendError
@@ -561,6 +1278,20 @@ export namespace Responses {
         "response": "FriendNotFoundError"
     }
 
+    export interface PortError extends Response {
+        "response": "PortError"
+        "error": Errors.PortError
+    }
+
+    export interface RestartError extends Response {
+        "response": "RestartError"
+        "error": Errors.RestartError
+    }
+
+    export interface FriendRequestNotFoundError extends Response {
+        "response": "FriendRequestNotFoundError"
+    }
+
     export interface ConferenceInviteError extends Response {
         "response": "ConferenceInviteError",
         "error": Errors.ConferenceInviteError
@@ -585,6 +1316,105 @@ export namespace Responses {
         "response": "ConferenceTitleError",
         "error": Errors.ConferenceTitleError
     }
+
+    export interface ConferenceNotFoundError extends Response {
+        "response": "ConferenceNotFoundError"
+    }
+
+    export interface CallError extends Response {
+        "response": "CallError",
+        "error": Errors.CallError
+    }
+
+    export interface AnswerError extends Response {
+        "response": "AnswerError",
+        "error": Errors.AnswerError
+    }
+
+    export interface CallControlError extends Response {
+        "response": "CallControlError",
+        "error": Errors.CallControlError
+    }
+
+    export interface BitRateSetError extends Response {
+        "response": "BitRateSetError",
+        "error": Errors.BitRateSetError
+    }
+
+    export interface SendFrameError extends Response {
+        "response": "SendFrameError",
+        "error": Errors.SendFrameError
+    }
+
+    export interface Group extends Response {
+        "response": "Group",
+        "group": number,
+    }
+
+    export interface GroupChatId extends Response {
+        "response": "GroupChatId",
+        "chat_id": string,
+    }
+
+    export interface GroupList extends Response {
+        "response": "GroupList",
+        "groups": GroupInfo[],
+    }
+
+    export interface GroupPeerList extends Response {
+        "response": "GroupPeerList",
+        "peers": GroupPeerInfo[],
+    }
+
+    export interface GroupTopic extends Response {
+        "response": "GroupTopic",
+        "topic": string,
+    }
+
+    export interface GroupNewError extends Response {
+        "response": "GroupNewError",
+        "error": Errors.GroupNewError,
+    }
+
+    export interface GroupJoinError extends Response {
+        "response": "GroupJoinError",
+        "error": Errors.GroupJoinError,
+    }
+
+    export interface GroupInviteError extends Response {
+        "response": "GroupInviteError",
+        "error": Errors.GroupInviteError,
+    }
+
+    export interface GroupLeaveError extends Response {
+        "response": "GroupLeaveError",
+        "error": Errors.GroupLeaveError,
+    }
+
+    export interface GroupQueryError extends Response {
+        "response": "GroupQueryError",
+        "error": Errors.GroupQueryError,
+    }
+
+    export interface GroupTopicError extends Response {
+        "response": "GroupTopicError",
+        "error": Errors.GroupTopicError,
+    }
+
+    export interface GroupSendError extends Response {
+        "response": "GroupSendError",
+        "error": Errors.GroupSendError,
+    }
+
+    export interface GroupModerationError extends Response {
+        "response": "GroupModerationError",
+        "error": Errors.GroupModerationError,
+    }
+
+    export interface GroupFounderError extends Response {
+        "response": "GroupFounderError",
+        "error": Errors.GroupFounderError,
+    }
 }
 
 export namespace Events {
@@ -592,9 +1422,9 @@ export namespace Events {
         "event": string
     }
 
-    export interface SecretKey extends Event {
-        "event": "SecretKey"
-        "secret_key": string
+    export interface SecretKeyDisclosed extends Event {
+        "event": "SecretKeyDisclosed"
+        "encrypted": boolean
     }
 
     export interface ConnectionStatus extends Event {
@@ -651,6 +1481,11 @@ export namespace Events {
         "message_id": number
     }
 
+    export interface FriendRequestsChanged extends Event {
+        "event": "FriendRequestsChanged",
+        "requests": PendingFriendRequest[]
+    }
+
     export interface FileControlReceipt extends Event {
         "event": "FileControlReceipt"
         "friend": number
@@ -670,7 +1505,7 @@ export namespace Events {
         "event": "FileReceipt"
         "friend": number
         "file_number": number
-        "kind": number
+        "kind": FileKind
         "file_size": number
         "file_name": string
     }
@@ -683,6 +1518,29 @@ export namespace Events {
         "data": string
     }
 
+    export interface FileTransferProgress extends Event {
+        "event": "FileTransferProgress"
+        "friend": number
+        "file_number": number
+        "transferred": number
+        "total": number
+        "rate": number
+    }
+
+    export interface FileResumed extends Event {
+        "event": "FileResumed"
+        "friend": number
+        "file_number": number
+        "file_id": string
+        "position": number
+    }
+
+    export interface FriendAvatarChanged extends Event {
+        "event": "FriendAvatarChanged"
+        "friend": number
+        "hash": string
+    }
+
     export interface ConferenceInvite extends Event {
         "event": "ConferenceInvite",
         "friend": number,
@@ -699,6 +1557,7 @@ export namespace Events {
         "event": "ConferenceMessage",
         "conference": number,
         "peer": number,
+        "public_key": string | null,
         "kind": MessageType,
         "message": string,
     }
@@ -707,6 +1566,7 @@ export namespace Events {
         "event": "ConferenceTitle",
         "conference": number,
         "peer": number,
+        "public_key": string | null,
         "title": string,
     }
 
@@ -714,6 +1574,7 @@ export namespace Events {
         "event": "ConferencePeerName",
         "conference": number,
         "peer": number,
+        "public_key": string | null,
         "name": string,
     }
 
@@ -721,9 +1582,172 @@ export namespace Events {
         "event": "ConferencePeerListChanged",
         "conference": number
     }
+
+    export interface ConferencePeerOffline extends Event {
+        "event": "ConferencePeerOffline",
+        "conference": number,
+        "peer": PeerInfo,
+    }
+
+    export interface ConferencePeerJoined extends Event {
+        "event": "ConferencePeerJoined",
+        "conference": number,
+        "peer": PeerInfo,
+    }
+
+    export interface ConferencePeerLeft extends Event {
+        "event": "ConferencePeerLeft",
+        "conference": number,
+        "peer": PeerInfo,
+    }
+
+    export interface CallIncoming extends Event {
+        "event": "CallIncoming",
+        "friend": number,
+        "audio_enabled": boolean,
+        "video_enabled": boolean,
+    }
+
+    export interface CallState extends Event {
+        "event": "CallState",
+        "friend": number,
+        "state": CallStateInfo,
+    }
+
+    export interface AudioBitRateSuggestion extends Event {
+        "event": "AudioBitRateSuggestion",
+        "friend": number,
+        "bit_rate": number,
+    }
+
+    export interface VideoBitRateSuggestion extends Event {
+        "event": "VideoBitRateSuggestion",
+        "friend": number,
+        "bit_rate": number,
+    }
+
+    export interface AudioFrameReceipt extends Event {
+        "event": "AudioFrameReceipt",
+        "friend": number,
+        /** Base64 of interleaved signed 16-bit little-endian samples */
+        "pcm": string,
+        "channels": number,
+        "sample_rate": number,
+    }
+
+    export interface VideoFrameReceipt extends Event {
+        "event": "VideoFrameReceipt",
+        "friend": number,
+        "width": number,
+        "height": number,
+        "y": string,
+        "u": string,
+        "v": string,
+    }
+
+    export interface GroupInvite extends Event {
+        "event": "GroupInvite",
+        "friend": number,
+        "invite_data": string,
+        "group_name": string,
+    }
+
+    export interface GroupSelfJoin extends Event {
+        "event": "GroupSelfJoin",
+        "group": number,
+    }
+
+    export interface GroupJoinFail extends Event {
+        "event": "GroupJoinFail",
+        "group": number,
+        "reason": GroupJoinFailReason,
+    }
+
+    export interface GroupPeerJoin extends Event {
+        "event": "GroupPeerJoin",
+        "group": number,
+        "peer": GroupPeerInfo,
+    }
+
+    export interface GroupPeerExit extends Event {
+        "event": "GroupPeerExit",
+        "group": number,
+        "peer": number,
+        "name": string,
+        "message": string,
+    }
+
+    export interface GroupPeerName extends Event {
+        "event": "GroupPeerName",
+        "group": number,
+        "peer": number,
+        "name": string,
+    }
+
+    export interface GroupTopic extends Event {
+        "event": "GroupTopic",
+        "group": number,
+        "peer": number,
+        "topic": string,
+    }
+
+    export interface GroupMessage extends Event {
+        "event": "GroupMessage",
+        "group": number,
+        "peer": number,
+        "kind": MessageType,
+        "message": string,
+    }
+
+    export interface GroupPrivateMessage extends Event {
+        "event": "GroupPrivateMessage",
+        "group": number,
+        "peer": number,
+        "kind": MessageType,
+        "message": string,
+    }
+
+    export interface GroupModeration extends Event {
+        "event": "GroupModeration",
+        "group": number,
+        "source_peer": number,
+        "target_peer": number,
+        "action": GroupModerationAction,
+    }
+
+    export interface GroupPrivacyState extends Event {
+        "event": "GroupPrivacyState",
+        "group": number,
+        "privacy": ToxGroupPrivacyState,
+    }
+
+    export interface GroupPassword extends Event {
+        "event": "GroupPassword",
+        "group": number,
+        "has_password": boolean,
+    }
 }
 
 export namespace Errors {
+    export type PortError = "NotBound";
+
+    export type RestartError =
+        { "UnsupportedOption": { "field": string } } |
+        "PortAlloc" |
+        "BadProxyHost" |
+        "BadProxyPort" |
+        "ProxyNotFound";
+
+    export type ProfileError =
+        "NotFound" |
+        "AlreadyExists" |
+        "AlreadyLoaded" |
+        "NotLoaded" |
+        "BadPassword" |
+        "LoadFailed";
+
+    export type ValidationError = "Empty" | "Malformed" | { "TooLong": { "max": number } };
+
     export type AddFriendError =
         "TooLong" |
         "NoMessage" |
@@ -766,4 +1790,105 @@ export namespace Errors {
         "ConferenceNotFound" |
         "InvalidLength" |
         "FailSend";
+
+    export type CallError =
+        "Malloc" |
+        "Sync" |
+        "FriendNotFound" |
+        "FriendNotConnected" |
+        "FriendAlreadyInCall" |
+        "InvalidBitRate";
+
+    export type AnswerError =
+        "Sync" |
+        "CodecInitialization" |
+        "FriendNotFound" |
+        "FriendNotCalling" |
+        "InvalidBitRate";
+
+    export type CallControlError =
+        "Sync" |
+        "FriendNotFound" |
+        "FriendNotInCall" |
+        "InvalidTransition";
+
+    export type BitRateSetError =
+        "Sync" |
+        "InvalidBitRate" |
+        "FriendNotFound" |
+        "FriendNotInCall";
+
+    export type SendFrameError =
+        "Null" |
+        "FriendNotFound" |
+        "FriendNotInCall" |
+        "Sync" |
+        "Invalid" |
+        "PayloadTypeDisabled" |
+        "RtpFailed";
+
+    export type GroupNewError =
+        "TooLong" |
+        "Empty" |
+        "Init" |
+        "State" |
+        "Announce";
+
+    export type GroupJoinError =
+        "Init" |
+        "BadChatId" |
+        "BadInvite" |
+        "FriendNotFound" |
+        "Empty" |
+        "TooLong" |
+        "Password" |
+        "Core";
+
+    export type GroupInviteError =
+        "GroupNotFound" |
+        "FriendNotFound" |
+        "FailSend" |
+        "Disconnected";
+
+    export type GroupLeaveError =
+        "GroupNotFound" |
+        "TooLong" |
+        "FailSend";
+
+    export type GroupQueryError =
+        "GroupNotFound" |
+        "PeerNotFound";
+
+    export type GroupTopicError =
+        "GroupNotFound" |
+        "TooLong" |
+        "Permissions" |
+        "FailCreate" |
+        "FailSend" |
+        "Disconnected";
+
+    export type GroupSendError =
+        "GroupNotFound" |
+        "PeerNotFound" |
+        "TooLong" |
+        "Empty" |
+        "Permissions" |
+        "FailSend" |
+        "Disconnected";
+
+    export type GroupModerationError =
+        "GroupNotFound" |
+        "PeerNotFound" |
+        "Permissions" |
+        "Assignment" |
+        "FailAction" |
+        "SelfAction";
+
+    export type GroupFounderError =
+        "GroupNotFound" |
+        "Permissions" |
+        "TooLong" |
+        "FailSet" |
+        "FailSend" |
+        "Disconnected";
 }
@@ -4,6 +4,13 @@ use base64_serde::base64_serde_type;
 
 use std::convert::TryFrom;
 
+pub mod rate_limit;
+pub mod roster;
+pub mod validation;
+
+use crate::roster::RosterResult;
+use crate::validation::ValidationError;
+
 base64_serde_type!(Base64, STANDARD);
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -12,27 +19,99 @@ pub enum ServerMessage {
     Event(Event),
 }
 
+/// A request addressed to one of the Tox profiles hosted by the server.
+/// Requests without a profile go to the default profile.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileRequest {
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(flatten)]
+    pub request: Request,
+}
+
+/// A response or event coming from one of the hosted Tox profiles.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileMessage {
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(flatten)]
+    pub message: Message,
+}
+
+/// A `ServerMessage` told apart by its own `response` or `event` tag, so
+/// that it can be flattened next to other fields.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Message {
+    Response(Response),
+    Event(Event),
+}
+
+impl From<ServerMessage> for Message {
+    fn from(message: ServerMessage) -> Message {
+        match message {
+            ServerMessage::Response(response) => Message::Response(response),
+            ServerMessage::Event(event) => Message::Event(event),
+        }
+    }
+}
+
+impl From<Message> for ServerMessage {
+    fn from(message: Message) -> ServerMessage {
+        match message {
+            Message::Response(response) => ServerMessage::Response(response),
+            Message::Event(event) => ServerMessage::Event(event),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "request")]
 pub enum Request {
+    Authenticate { token: String },
+    ListProfiles,
+    CreateProfile { name: String, password: Option<String> },
+    LoadProfile { id: String, password: Option<String> },
+    UnloadProfile { id: String },
     Info,
     SetInfo {
         nospam: String,
         name: String,
         status: UserStatus,
         status_message: String,
-        friends: Vec<String>
+        /// The complete desired friend list as public keys or Tox IDs.
+        /// Friends missing from it are deleted and new keys are added
+        /// without a request; the outcome is reported in `RosterSync`.
+        /// `None` leaves the friend list alone.
+        #[serde(default)]
+        friends: Option<Vec<String>>
     },
     AddFriend { tox_id: String, message: String },
     AddFriendNorequest { tox_id: String },
     SendFriendMessage { friend: u32, kind: MessageType, message: String },
     DeleteFriend { friend: u32 },
+    ListFriendRequests,
+    AcceptFriendRequest { public_key: String },
+    RejectFriendRequest { public_key: String },
+    /// `public_key` may be a public key or a Tox ID; the block list keeps
+    /// the public key from `normalize_public_key`.
+    Block { public_key: String },
+    Unblock { public_key: String },
+    GetBlockList,
 
     GetConnectionStatus,
+    GetDhtId,
+    GetUdpPort,
+    GetTcpPort,
+    GetNodeStatus,
+    GetOptions,
+    RestartWithOptions { options: ToxOptions },
     GetAddress,
     GetNospam,
     SetNospam { nospam: String },
     GetPublicKey,
+    GetSecretKey,
+    ExportSecretKey { passphrase: String },
     SetName { name: String },
     GetName,
     SetStatusMessage { message: String },
@@ -47,20 +126,32 @@ pub enum Request {
     GetFriendStatusMessage { friend: u32 },
     GetFriendStatus { friend: u32 },
     GetFriendConnectionStatus { friend: u32 },
+    GetFriendList,
+    GetFriend { friend: u32 },
+    GetFriendMetadata { public_key: String },
+    SetFriendMetadata { public_key: String, metadata: LocalMetadata },
 
     ControlFile { friend: u32, file_number: u32, control: FileControl },
-    SeekFile { friend: u32, file_number: u32, position: usize },
+    SeekFile { friend: u32, file_number: u32, position: u64 },
     GetFileId { friend: u32, file_number: u32 },
-    SendFile { friend: u32, kind: FileKind, file_size: usize, file_name: String },
+    SendFile { friend: u32, kind: FileKind, file_size: u64, file_name: String },
     SendFileChunk {
         friend: u32,
         file_number: u32,
-        position: usize,
+        position: u64,
         #[serde(with = "Base64")]
         data: Vec<u8>
     },
+    GetFileTransfers,
+    GetPartialFiles,
+    ForgetPartialFile { file_id: String },
 
-    SendAvatar { friend: u32, file_size: usize, file_hash: String },
+    SendAvatar { friend: u32, file_size: u64, file_hash: String },
+    SetAvatar {
+        #[serde(with = "Base64")]
+        data: Vec<u8>
+    },
+    GetAvatar { friend: u32 },
 
     NewConference,
     DeleteConference { conference: u32 },
@@ -68,6 +159,11 @@ pub enum Request {
     ConferencePeerCount { conference: u32, },
     GetPeerName { conference: u32, peer: u32 },
     GetPeerPublicKey { conference: u32, peer: u32 },
+    GetOfflinePeerList { conference: u32 },
+    ConferenceOfflinePeerCount { conference: u32 },
+    GetOfflinePeerName { conference: u32, peer: u32 },
+    GetOfflinePeerPublicKey { conference: u32, peer: u32 },
+    GetOfflinePeerLastActive { conference: u32, peer: u32 },
     IsOwnPeerNumber { conference: u32, peer_number: u32 },
     InviteToConference { friend: u32, conference: u32 },
     JoinConference {
@@ -80,12 +176,72 @@ pub enum Request {
     SetConferenceTitle { conference: u32, title: String },
     GetConferenceList,
     GetConferenceType { conference: u32 },
+    GetConferenceId { conference: u32 },
+    GetConferenceById { id: String },
+
+    NewGroup { privacy: GroupPrivacyState, name: String, self_name: String },
+    JoinGroup { chat_id: String, self_name: String, password: Option<String> },
+    AcceptGroupInvite {
+        friend: u32,
+        #[serde(with = "Base64")]
+        invite_data: Vec<u8>,
+        self_name: String,
+        password: Option<String>
+    },
+    InviteToGroup { friend: u32, group: u32 },
+    LeaveGroup { group: u32, message: String },
+    GetGroupList,
+    GetGroupChatId { group: u32 },
+    GetGroupPeerList { group: u32 },
+    GetGroupTopic { group: u32 },
+    SetGroupTopic { group: u32, topic: String },
+    SendGroupMessage { group: u32, kind: MessageType, message: String },
+    SendGroupPrivateMessage { group: u32, peer: u32, kind: MessageType, message: String },
+    SetGroupRole { group: u32, peer: u32, role: GroupRole },
+    KickGroupPeer { group: u32, peer: u32 },
+    BanGroupPeer { group: u32, peer: u32 },
+    SetGroupPassword { group: u32, password: Option<String> },
+    SetGroupPrivacyState { group: u32, privacy: GroupPrivacyState },
+
+    Call { friend: u32, audio_bit_rate: u32, video_bit_rate: u32 },
+    Answer { friend: u32, audio_bit_rate: u32, video_bit_rate: u32 },
+    CallControl { friend: u32, control: CallControl },
+    SetAudioBitRate { friend: u32, bit_rate: u32 },
+    SetVideoBitRate { friend: u32, bit_rate: u32 },
+    SendAudioFrame {
+        friend: u32,
+        /// Interleaved signed 16-bit little-endian samples.
+        #[serde(with = "Base64")]
+        pcm: Vec<u8>,
+        channels: u8,
+        sample_rate: u32
+    },
+    SendVideoFrame {
+        friend: u32,
+        width: u16,
+        height: u16,
+        #[serde(with = "Base64")]
+        y: Vec<u8>,
+        #[serde(with = "Base64")]
+        u: Vec<u8>,
+        #[serde(with = "Base64")]
+        v: Vec<u8>
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "response")]
 pub enum Response {
     Ok,
+    Unauthenticated,
+    AuthenticationFailed,
+    PermissionDenied { scope: Scope },
+    ValidationError { field: String, error: ValidationError },
+    RateLimited { retry_after_ms: u64 },
+
+    Profiles { profiles: Vec<ProfileInfo> },
+    Profile { id: String },
+    ProfileError { error: ProfileError },
     MessageSent {
         message_id: u32,
     },
@@ -98,48 +254,98 @@ pub enum Response {
     },
 
     ConnectionStatus { status: ConnectionStatus },
+    DhtId { dht_id: String },
+    Port { port: u16 },
+    NodeStatus { status: NodeStatus },
+    Options { options: ToxOptions },
     Address { address: String },
     Nospam { nospam: String },
     PublicKey { public_key: String },
+    SecretKey { secret_key: String },
+    EncryptedSecretKey {
+        #[serde(with = "Base64")]
+        data: Vec<u8>
+    },
     Name { name: String },
     StatusMessage { status: String },
     Status { status: UserStatus },
     Friend { friend: u32 },
     FriendExists { exists: bool },
     LastOnline { last_online: u64 },
+    FriendList { friends: Vec<Friend> },
+    FriendRecord { friend: Friend },
+    RosterSync { results: Vec<RosterResult> },
+    FriendMetadata { metadata: LocalMetadata },
+    FriendRequests { requests: Vec<PendingFriendRequest> },
+    BlockList { public_keys: Vec<String> },
 
     FileId { id: String },
     FileNumber { file_number: u32 },
+    FileTransfers { transfers: Vec<FileTransfer> },
+    PartialFiles { files: Vec<PartialFile> },
     FileControlError { error: FileControlError },
     FileSeekError { error: FileSeekError },
     FileGetError { error: FileGetError },
     FileSendError { error: FileSendError },
     FileSendChunkError { error: FileSendChunkError },
 
+    Avatar {
+        hash: String,
+        #[serde(with = "Base64")]
+        data: Vec<u8>
+    },
+    AvatarNotFoundError,
+
     Conference { conference: u32 },
     ConferencePeerList { peers: Vec<PeerInfo> },
     ConferencePeerCount { count: u32 },
     ConferencePeerName { name: String },
     ConferencePeerPublicKey { public_key: String },
+    ConferencePeerLastActive { last_active: u64 },
     IsOwnPeerNumber { is_own: bool, },
     ConferenceTitle { title: String, },
     ConferenceList { conferences: Vec<ConferenceInfo> },
     ConferenceType { kind: ConferenceType },
+    ConferenceId { id: String },
+
+    Group { group: u32 },
+    GroupChatId { chat_id: String },
+    GroupList { groups: Vec<GroupInfo> },
+    GroupPeerList { peers: Vec<GroupPeerInfo> },
+    GroupTopic { topic: String },
 
     AddFriendError { error: AddFriendError },
     SendFriendMessageError { error: SendFriendMessageError },
     FriendNotFoundError,
+    PortError { error: PortError },
+    RestartError { error: RestartError },
+    FriendRequestNotFoundError,
     ConferenceInviteError { error: ConferenceInviteError },
     ConferenceJoinError { error: ConferenceJoinError },
     ConferencePeerQueryError { error: ConferencePeerQueryError },
     ConferenceSendError { error: ConferenceSendError },
     ConferenceTitleError { error: ConferenceTitleError },
+    ConferenceNotFoundError,
+    GroupNewError { error: GroupNewError },
+    GroupJoinError { error: GroupJoinError },
+    GroupInviteError { error: GroupInviteError },
+    GroupLeaveError { error: GroupLeaveError },
+    GroupQueryError { error: GroupQueryError },
+    GroupTopicError { error: GroupTopicError },
+    GroupSendError { error: GroupSendError },
+    GroupModerationError { error: GroupModerationError },
+    GroupFounderError { error: GroupFounderError },
+    CallError { error: CallError },
+    AnswerError { error: AnswerError },
+    CallControlError { error: CallControlError },
+    BitRateSetError { error: BitRateSetError },
+    SendFrameError { error: SendFrameError },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "event")]
 pub enum Event {
-    SecretKey { secret_key: String },
+    SecretKeyDisclosed { encrypted: bool },
     ConnectionStatus { status: ConnectionStatus },
     FriendRequest { public_key: [u8; 32], message: String },
     FriendMessage { friend: u32, kind: MessageType, message: String },
@@ -149,6 +355,7 @@ pub enum Event {
     FriendConnectionStatus { friend: u32, status: ConnectionStatus },
     FriendTyping { friend: u32, is_typing: bool },
     FriendReadReceipt { friend: u32, message_id: u32 },
+    FriendRequestsChanged { requests: Vec<PendingFriendRequest> },
 
     FileControlReceipt {
         friend: u32,
@@ -158,23 +365,38 @@ pub enum Event {
     FileChunkRequest {
         friend: u32,
         file_number: u32,
-        position: usize,
-        length: usize
+        position: u64,
+        length: u64
     },
     FileReceipt {
         friend: u32,
         file_number: u32,
-        kind: u32,
-        file_size: usize,
+        kind: FileKind,
+        file_size: u64,
         file_name: String
     },
     FileChunkReceipt {
         friend: u32,
         file_number: u32,
-        position: usize,
+        position: u64,
         #[serde(with = "Base64")]
         data: Vec<u8>
     },
+    FileTransferProgress {
+        friend: u32,
+        file_number: u32,
+        transferred: u64,
+        total: u64,
+        rate: u64
+    },
+    FileResumed {
+        friend: u32,
+        file_number: u32,
+        file_id: String,
+        position: u64
+    },
+
+    FriendAvatarChanged { friend: u32, hash: String },
 
     ConferenceInvite {
         friend: u32,
@@ -188,27 +410,308 @@ pub enum Event {
     ConferenceMessage {
         conference: u32,
         peer: u32,
+        public_key: Option<String>,
         kind: MessageType,
         message: String,
     },
     ConferenceTitle {
         conference: u32,
         peer: u32,
+        public_key: Option<String>,
         title: String,
     },
     ConferencePeerName {
         conference: u32,
         peer: u32,
+        public_key: Option<String>,
         name: String,
     },
     ConferencePeerListChanged {
         conference: u32
     },
+    ConferencePeerOffline {
+        conference: u32,
+        peer: PeerInfo,
+    },
+    ConferencePeerJoined {
+        conference: u32,
+        peer: PeerInfo,
+    },
+    ConferencePeerLeft {
+        conference: u32,
+        peer: PeerInfo,
+    },
+
+    GroupInvite {
+        friend: u32,
+        #[serde(with = "Base64")]
+        invite_data: Vec<u8>,
+        group_name: String,
+    },
+    GroupSelfJoin {
+        group: u32
+    },
+    GroupJoinFail {
+        group: u32,
+        reason: GroupJoinFailReason,
+    },
+    GroupPeerJoin {
+        group: u32,
+        peer: GroupPeerInfo,
+    },
+    GroupPeerExit {
+        group: u32,
+        peer: u32,
+        name: String,
+        message: String,
+    },
+    GroupPeerName {
+        group: u32,
+        peer: u32,
+        name: String,
+    },
+    GroupTopic {
+        group: u32,
+        peer: u32,
+        topic: String,
+    },
+    GroupMessage {
+        group: u32,
+        peer: u32,
+        kind: MessageType,
+        message: String,
+    },
+    GroupPrivateMessage {
+        group: u32,
+        peer: u32,
+        kind: MessageType,
+        message: String,
+    },
+    GroupModeration {
+        group: u32,
+        source_peer: u32,
+        target_peer: u32,
+        action: GroupModerationAction,
+    },
+    GroupPrivacyState {
+        group: u32,
+        privacy: GroupPrivacyState,
+    },
+    GroupPassword {
+        group: u32,
+        has_password: bool,
+    },
+
+    CallIncoming {
+        friend: u32,
+        audio_enabled: bool,
+        video_enabled: bool,
+    },
+    CallState {
+        friend: u32,
+        state: CallState,
+    },
+    AudioBitRateSuggestion {
+        friend: u32,
+        bit_rate: u32,
+    },
+    VideoBitRateSuggestion {
+        friend: u32,
+        bit_rate: u32,
+    },
+    AudioFrameReceipt {
+        friend: u32,
+        /// Interleaved signed 16-bit little-endian samples.
+        #[serde(with = "Base64")]
+        pcm: Vec<u8>,
+        channels: u8,
+        sample_rate: u32,
+    },
+    VideoFrameReceipt {
+        friend: u32,
+        width: u16,
+        height: u16,
+        #[serde(with = "Base64")]
+        y: Vec<u8>,
+        #[serde(with = "Base64")]
+        u: Vec<u8>,
+        #[serde(with = "Base64")]
+        v: Vec<u8>,
+    },
+}
+
+impl Request {
+    /// Whether the request may only be issued after a successful
+    /// `Authenticate`.
+    pub fn requires_authentication(&self) -> bool {
+        self.scope().is_some()
+    }
+
+    /// The friend the request is addressed to, if any.
+    pub fn friend(&self) -> Option<u32> {
+        match *self {
+            Request::SendFriendMessage { friend, .. }
+            | Request::DeleteFriend { friend, .. }
+            | Request::FriendExists { friend, .. }
+            | Request::GetFriendPublicKey { friend, .. }
+            | Request::GetFriendLastOnline { friend, .. }
+            | Request::GetFriendName { friend, .. }
+            | Request::GetFriendStatusMessage { friend, .. }
+            | Request::GetFriendStatus { friend, .. }
+            | Request::GetFriendConnectionStatus { friend, .. }
+            | Request::GetFriend { friend, .. }
+            | Request::ControlFile { friend, .. }
+            | Request::SeekFile { friend, .. }
+            | Request::GetFileId { friend, .. }
+            | Request::SendFile { friend, .. }
+            | Request::SendFileChunk { friend, .. }
+            | Request::SendAvatar { friend, .. }
+            | Request::GetAvatar { friend, .. }
+            | Request::InviteToConference { friend, .. }
+            | Request::JoinConference { friend, .. }
+            | Request::AcceptGroupInvite { friend, .. }
+            | Request::InviteToGroup { friend, .. }
+            | Request::Call { friend, .. }
+            | Request::Answer { friend, .. }
+            | Request::CallControl { friend, .. }
+            | Request::SetAudioBitRate { friend, .. }
+            | Request::SetVideoBitRate { friend, .. }
+            | Request::SendAudioFrame { friend, .. }
+            | Request::SendVideoFrame { friend, .. } => Some(friend),
+            _ => None,
+        }
+    }
+
+    /// The permission scope a client needs to issue the request, or `None`
+    /// for requests that are allowed before authentication.
+    pub fn scope(&self) -> Option<Scope> {
+        match *self {
+            Request::Authenticate { .. } => None,
+            Request::Info
+            | Request::ListFriendRequests
+            | Request::GetBlockList
+            | Request::GetConnectionStatus
+            | Request::GetDhtId
+            | Request::GetUdpPort
+            | Request::GetTcpPort
+            | Request::GetNodeStatus
+            | Request::GetOptions
+            | Request::GetAddress
+            | Request::GetNospam
+            | Request::GetPublicKey
+            | Request::GetName
+            | Request::GetStatusMessage
+            | Request::GetStatus
+            | Request::FriendByPublicKey { .. }
+            | Request::FriendExists { .. }
+            | Request::GetFriendPublicKey { .. }
+            | Request::GetFriendLastOnline { .. }
+            | Request::GetFriendName { .. }
+            | Request::GetFriendStatusMessage { .. }
+            | Request::GetFriendStatus { .. }
+            | Request::GetFriendConnectionStatus { .. }
+            | Request::GetFriendList
+            | Request::GetFriend { .. }
+            | Request::GetFriendMetadata { .. }
+            | Request::GetFileId { .. }
+            | Request::GetFileTransfers
+            | Request::GetPartialFiles
+            | Request::GetAvatar { .. }
+            | Request::GetPeerList { .. }
+            | Request::ConferencePeerCount { .. }
+            | Request::GetPeerName { .. }
+            | Request::GetPeerPublicKey { .. }
+            | Request::GetOfflinePeerList { .. }
+            | Request::ConferenceOfflinePeerCount { .. }
+            | Request::GetOfflinePeerName { .. }
+            | Request::GetOfflinePeerPublicKey { .. }
+            | Request::GetOfflinePeerLastActive { .. }
+            | Request::IsOwnPeerNumber { .. }
+            | Request::GetConferenceTitle { .. }
+            | Request::GetConferenceList
+            | Request::GetConferenceType { .. }
+            | Request::GetConferenceId { .. }
+            | Request::GetConferenceById { .. }
+            | Request::GetGroupList
+            | Request::GetGroupChatId { .. }
+            | Request::GetGroupPeerList { .. }
+            | Request::GetGroupTopic { .. } => Some(Scope::Read),
+
+            Request::SendFriendMessage { .. }
+            | Request::NewConference
+            | Request::InviteToConference { .. }
+            | Request::JoinConference { .. }
+            | Request::SendConferenceMessage { .. }
+            | Request::SetConferenceTitle { .. }
+            | Request::NewGroup { .. }
+            | Request::JoinGroup { .. }
+            | Request::AcceptGroupInvite { .. }
+            | Request::InviteToGroup { .. }
+            | Request::LeaveGroup { .. }
+            | Request::SetGroupTopic { .. }
+            | Request::SendGroupMessage { .. }
+            | Request::SendGroupPrivateMessage { .. }
+            | Request::Call { .. }
+            | Request::Answer { .. }
+            | Request::CallControl { .. }
+            | Request::SetAudioBitRate { .. }
+            | Request::SetVideoBitRate { .. }
+            | Request::SendAudioFrame { .. }
+            | Request::SendVideoFrame { .. } => Some(Scope::Message),
+
+            Request::ControlFile { .. }
+            | Request::SeekFile { .. }
+            | Request::SendFile { .. }
+            | Request::SendFileChunk { .. }
+            | Request::ForgetPartialFile { .. }
+            | Request::SendAvatar { .. }
+            | Request::SetAvatar { .. } => Some(Scope::File),
+
+            Request::ListProfiles
+            | Request::CreateProfile { .. }
+            | Request::LoadProfile { .. }
+            | Request::UnloadProfile { .. }
+            | Request::RestartWithOptions { .. }
+            | Request::SetInfo { .. }
+            | Request::AddFriend { .. }
+            | Request::AddFriendNorequest { .. }
+            | Request::DeleteFriend { .. }
+            | Request::AcceptFriendRequest { .. }
+            | Request::RejectFriendRequest { .. }
+            | Request::Block { .. }
+            | Request::Unblock { .. }
+            | Request::SetNospam { .. }
+            | Request::SetName { .. }
+            | Request::SetStatusMessage { .. }
+            | Request::SetStatus { .. }
+            | Request::SetFriendMetadata { .. }
+            | Request::DeleteConference { .. }
+            | Request::SetGroupRole { .. }
+            | Request::KickGroupPeer { .. }
+            | Request::BanGroupPeer { .. }
+            | Request::SetGroupPassword { .. }
+            | Request::SetGroupPrivacyState { .. } => Some(Scope::Admin),
+
+            Request::GetSecretKey
+            | Request::ExportSecretKey { .. } => Some(Scope::Secrets),
+        }
+    }
 }
 
 impl Event {
+    /// Converts without conference peer public keys; see
+    /// `from_tox_event_with_peers`.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn from_tox_event(event: &rstox::core::Event) -> Option<Self> {
+        Event::from_tox_event_with_peers(event, |_, _| None)
+    }
+
+    /// `peer_public_key` resolves a conference and peer number pair to the
+    /// peer's public key at the time the event is received.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_tox_event_with_peers<F>(event: &rstox::core::Event, peer_public_key: F) -> Option<Self>
+        where F: Fn(u32, u32) -> Option<String>
+    {
         use rstox::core::Event as E;
 
         Some(match *event {
@@ -261,22 +764,22 @@ impl Event {
                 Event::FileChunkRequest {
                     friend,
                     file_number,
-                    position,
-                    length
+                    position: u64::try_from(position).ok()?,
+                    length: u64::try_from(length).ok()?
                 },
             E::FileReceipt { friend, file_number, kind, file_size, ref file_name } =>
                 Event::FileReceipt {
                     friend,
                     file_number,
                     kind: kind.into(),
-                    file_size,
+                    file_size: u64::try_from(file_size).ok()?,
                     file_name: file_name.clone()
                 },
             E::FileChunkReceipt { friend, file_number, position, ref data } =>
                 Event::FileChunkReceipt {
                     friend,
                     file_number,
-                    position,
+                    position: u64::try_from(position).ok()?,
                     data: data.clone()
                 },
 
@@ -294,6 +797,7 @@ impl Event {
                 Event::ConferenceMessage {
                     conference,
                     peer,
+                    public_key: peer_public_key(conference, peer),
                     kind: kind.into(),
                     message: message.clone()
                 },
@@ -301,12 +805,14 @@ impl Event {
                 Event::ConferenceTitle {
                     conference,
                     peer,
+                    public_key: peer_public_key(conference, peer),
                     title: title.clone()
                 },
             E::ConferencePeerName { conference, peer, ref name } =>
                 Event::ConferencePeerName {
                     conference,
                     peer,
+                    public_key: peer_public_key(conference, peer),
                     name: name.clone()
                 },
             E::ConferencePeerListChanged { conference } =>
@@ -316,6 +822,256 @@ impl Event {
             _ => return None,
         })
     }
+
+    /// Compares two snapshots of a conference peer list and produces
+    /// `ConferencePeerJoined` and `ConferencePeerLeft` events. Peers are
+    /// matched by public key, since peer numbers change when peers leave.
+    pub fn from_peer_list_diff(conference: u32, old: &[PeerInfo], new: &[PeerInfo]) -> Vec<Self> {
+        let left = old.iter()
+            .filter(|peer| !new.iter().any(|p| p.public_key == peer.public_key))
+            .map(|peer| Event::ConferencePeerLeft { conference, peer: peer.clone() });
+        let joined = new.iter()
+            .filter(|peer| !old.iter().any(|p| p.public_key == peer.public_key))
+            .map(|peer| Event::ConferencePeerJoined { conference, peer: peer.clone() });
+
+        left.chain(joined).collect()
+    }
+
+    /// Checks whether the event originates from a blocked public key and
+    /// should not be delivered. `blocked` may hold public keys or Tox IDs;
+    /// `friend_public_key` resolves a friend number to the friend's public key.
+    pub fn is_blocked<F>(&self, blocked: &[String], friend_public_key: F) -> bool
+        where F: Fn(u32) -> Option<String>
+    {
+        let sender = match *self {
+            Event::FriendRequest { ref public_key, .. } =>
+                Some(public_key.iter().map(|b| format!("{:02X}", b)).collect()),
+            Event::FriendMessage { friend, .. }
+            | Event::FileReceipt { friend, .. }
+            | Event::ConferenceInvite { friend, .. }
+            | Event::GroupInvite { friend, .. }
+            | Event::CallIncoming { friend, .. } =>
+                friend_public_key(friend),
+            Event::ConferenceMessage { ref public_key, .. } =>
+                public_key.clone(),
+            _ => None,
+        };
+
+        match sender.as_deref().and_then(normalize_public_key) {
+            Some(sender) => blocked.iter()
+                .any(|key| normalize_public_key(key).as_ref() == Some(&sender)),
+            None => false,
+        }
+    }
+}
+
+/// Converts a file size, position or length from the wire into the width
+/// rstox expects, failing instead of truncating when it doesn't fit.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn to_tox_size(value: u64) -> Option<usize> {
+    usize::try_from(value).ok()
+}
+
+const PUBLIC_KEY_HEX_LENGTH: usize = 64;
+const TOX_ID_HEX_LENGTH: usize = 76;
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None
+    }
+
+    hex.as_bytes().chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+/// Takes the public key part of a public key or a full Tox ID with a valid
+/// checksum, uppercased, or `None` if `key` is neither.
+pub fn normalize_public_key(key: &str) -> Option<String> {
+    if key.len() != PUBLIC_KEY_HEX_LENGTH && key.len() != TOX_ID_HEX_LENGTH {
+        return None
+    }
+
+    let bytes = parse_hex(key)?;
+
+    if key.len() == TOX_ID_HEX_LENGTH {
+        let (id, checksum) = bytes.split_at(bytes.len() - 2);
+        let mut expected = [0; 2];
+        for (i, b) in id.iter().enumerate() {
+            expected[i % 2] ^= b;
+        }
+
+        if checksum != expected {
+            return None
+        }
+    }
+
+    Some(key[.. PUBLIC_KEY_HEX_LENGTH].to_ascii_uppercase())
+}
+
+/// Number of samples per channel in an audio frame of interleaved 16-bit
+/// samples, or `None` if `pcm` doesn't hold a whole number of them.
+pub fn pcm_sample_count(pcm: &[u8], channels: u8) -> Option<usize> {
+    let frame_size = 2 * usize::from(channels);
+
+    if frame_size == 0 || !pcm.len().is_multiple_of(frame_size) {
+        None
+    } else {
+        Some(pcm.len() / frame_size)
+    }
+}
+
+pub fn pcm_from_samples(samples: &[i16]) -> Vec<u8> {
+    samples.iter().flat_map(|sample| sample.to_le_bytes()).collect()
+}
+
+/// `None` if `pcm` has an odd length.
+pub fn pcm_to_samples(pcm: &[u8]) -> Option<Vec<i16>> {
+    if !pcm.len().is_multiple_of(2) {
+        return None
+    }
+
+    Some(pcm.chunks(2).map(|pair| i16::from_le_bytes([pair[0], pair[1]])).collect())
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Read,
+    Message,
+    File,
+    Admin,
+    Secrets,
+}
+
+/// An access token and the scopes granted to clients authenticating with it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TokenConfig {
+    pub token: String,
+    pub scopes: Vec<Scope>,
+}
+
+impl TokenConfig {
+    /// Compares `token` with the configured one in time that depends only
+    /// on the configured token's length.
+    pub fn matches(&self, token: &str) -> bool {
+        let expected = self.token.as_bytes();
+        let token = token.as_bytes();
+
+        let diff = expected.iter().enumerate()
+            .fold(u8::from(expected.len() != token.len()), |diff, (i, &b)| {
+                diff | (b ^ token.get(i).copied().unwrap_or(0))
+            });
+
+        std::hint::black_box(diff) == 0
+    }
+
+    /// Whether a client holding this token may issue `request`.
+    pub fn allows(&self, request: &Request) -> bool {
+        match request.scope() {
+            Some(scope) => self.scopes.contains(&scope),
+            None => true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ProfileInfo {
+    pub id: String,
+    pub name: String,
+    pub public_key: String,
+    pub loaded: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NodeStatus {
+    pub connection: ConnectionStatus,
+    pub dht_nodes: u32,
+    pub tcp_relays: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ToxOptions {
+    pub ipv6_enabled: bool,
+    pub udp_enabled: bool,
+    pub local_discovery_enabled: bool,
+    pub hole_punching_enabled: bool,
+    pub proxy: Option<Proxy>,
+    pub start_port: u16,
+    pub end_port: u16,
+    pub tcp_port: u16,
+}
+
+impl Default for ToxOptions {
+    fn default() -> ToxOptions {
+        ToxOptions {
+            ipv6_enabled: true,
+            udp_enabled: true,
+            local_discovery_enabled: true,
+            hole_punching_enabled: true,
+            proxy: None,
+            start_port: 33445,
+            end_port: 33545,
+            tcp_port: 0,
+        }
+    }
+}
+
+impl ToxOptions {
+    /// The first field that differs from the toxcore default but can't be
+    /// set through the rstox options builder.
+    pub fn unsupported_option(&self) -> Option<&'static str> {
+        let default = ToxOptions::default();
+
+        if !self.ipv6_enabled {
+            Some("ipv6_enabled")
+        } else if !self.local_discovery_enabled {
+            Some("local_discovery_enabled")
+        } else if !self.hole_punching_enabled {
+            Some("hole_punching_enabled")
+        } else if self.start_port != default.start_port {
+            Some("start_port")
+        } else if self.end_port != default.end_port {
+            Some("end_port")
+        } else if self.tcp_port != default.tcp_port {
+            Some("tcp_port")
+        } else {
+            None
+        }
+    }
+}
+
+/// Fails with `UnsupportedOption` rather than silently dropping a setting
+/// rstox can't apply.
+#[cfg(not(target_arch = "wasm32"))]
+impl TryFrom<ToxOptions> for rstox::core::ToxOptions {
+    type Error = RestartError;
+
+    fn try_from(options: ToxOptions) -> Result<rstox::core::ToxOptions, RestartError> {
+        if let Some(field) = options.unsupported_option() {
+            return Err(RestartError::UnsupportedOption { field: field.to_owned() })
+        }
+
+        let mut tox_options = rstox::core::ToxOptions::new();
+
+        if options.ipv6_enabled {
+            tox_options = tox_options.ipv6();
+        }
+        if !options.udp_enabled {
+            tox_options = tox_options.no_udp();
+        }
+        if let Some(proxy) = options.proxy {
+            tox_options = tox_options.proxy(proxy.kind.into(), &proxy.host, proxy.port);
+        }
+
+        Ok(tox_options)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Proxy {
+    pub kind: ProxyType,
+    pub host: String,
+    pub port: u16,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -326,6 +1082,30 @@ pub struct Friend {
     pub status: UserStatus,
     pub status_message: String,
     pub last_online: u64,
+    #[serde(default)]
+    pub connection_status: ConnectionStatus,
+    #[serde(default)]
+    pub is_typing: bool,
+    #[serde(default)]
+    pub metadata: LocalMetadata,
+    /// Whether the friend's public key is on the block list.
+    #[serde(default)]
+    pub blocked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct LocalMetadata {
+    pub alias: Option<String>,
+    pub notes: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PendingFriendRequest {
+    pub public_key: String,
+    pub message: String,
+    pub received: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -333,18 +1113,79 @@ pub struct PeerInfo {
     pub number: u32,
     pub public_key: String,
     pub name: String,
+    pub online: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ConferenceInfo {
     pub number: u32,
+    pub id: String,
     pub kind: ConferenceType,
     pub title: String,
     pub peers: Vec<PeerInfo>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GroupPeerInfo {
+    pub number: u32,
+    pub public_key: String,
+    pub name: String,
+    pub role: GroupRole,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GroupInfo {
+    pub number: u32,
+    pub chat_id: String,
+    pub name: String,
+    pub topic: String,
+    pub privacy: GroupPrivacyState,
+    pub role: GroupRole,
+    pub peers: Vec<GroupPeerInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FileTransfer {
+    pub friend: u32,
+    pub file_number: u32,
+    pub direction: FileDirection,
+    pub kind: FileKind,
+    pub state: FileTransferState,
+    pub file_name: String,
+    pub transferred: u64,
+    pub total: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PartialFile {
+    pub file_id: String,
+    pub kind: FileKind,
+    pub file_name: String,
+    pub file_size: u64,
+    pub position: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum ProxyType {
+    Http,
+    Socks5,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<ProxyType> for rstox::core::ProxyType {
+    fn from(ty: ProxyType) -> rstox::core::ProxyType {
+        use rstox::core::ProxyType as P;
+
+        match ty {
+            ProxyType::Http => P::Http,
+            ProxyType::Socks5 => P::Socks5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub enum ConnectionStatus {
+    #[default]
     None,
     Tcp,
     Udp,
@@ -449,16 +1290,65 @@ impl From<rstox::core::FileControl> for FileControl {
 pub enum FileKind {
     Data,
     Avatar,
+    Unknown(u32),
+}
+
+impl From<u32> for FileKind {
+    fn from(kind: u32) -> FileKind {
+        match kind {
+            0 => FileKind::Data,
+            1 => FileKind::Avatar,
+            kind => FileKind::Unknown(kind),
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<rstox::core::FileKind> for FileKind {
+    fn from(ty: rstox::core::FileKind) -> FileKind {
+        use rstox::core::FileKind as K;
+
+        match ty {
+            K::Data => FileKind::Data,
+            K::Avatar => FileKind::Avatar,
+        }
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-impl From<FileKind> for rstox::core::FileKind {
-    fn from(ty: FileKind) -> rstox::core::FileKind {
+impl TryFrom<FileKind> for rstox::core::FileKind {
+    type Error = ();
+
+    fn try_from(ty: FileKind) -> Result<rstox::core::FileKind, ()> {
         use rstox::core::FileKind as K;
 
         match ty {
-            FileKind::Data => K::Data,
-            FileKind::Avatar => K::Avatar,
+            FileKind::Data => Ok(K::Data),
+            FileKind::Avatar => Ok(K::Avatar),
+            FileKind::Unknown(_) => Err(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum FileDirection {
+    Incoming,
+    Outgoing,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum FileTransferState {
+    Running,
+    Paused,
+    Cancelled,
+}
+
+impl From<FileControl> for FileTransferState {
+    fn from(control: FileControl) -> FileTransferState {
+        match control {
+            FileControl::Resume => FileTransferState::Running,
+            FileControl::Pause => FileTransferState::Paused,
+            FileControl::Cancel => FileTransferState::Cancelled,
         }
     }
 }
@@ -494,6 +1384,95 @@ impl From<ConferenceType> for rstox::core::ConferenceType {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupRole {
+    Founder,
+    Moderator,
+    User,
+    Observer,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupPrivacyState {
+    Public,
+    Private,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupModerationAction {
+    Kick,
+    Ban,
+    Observer,
+    User,
+    Moderator,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupJoinFailReason {
+    PeerLimit,
+    InvalidPassword,
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum CallControl {
+    Resume,
+    Pause,
+    Cancel,
+    MuteAudio,
+    UnmuteAudio,
+    HideVideo,
+    ShowVideo,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct CallState {
+    pub error: bool,
+    pub finished: bool,
+    pub sending_audio: bool,
+    pub sending_video: bool,
+    pub accepting_audio: bool,
+    pub accepting_video: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum PortError {
+    NotBound,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl TryFrom<rstox::core::errors::GetPortError> for PortError {
+    type Error = ();
+
+    fn try_from(error: rstox::core::errors::GetPortError) -> Result<PortError, ()> {
+        use rstox::core::errors::GetPortError as E;
+
+        match error {
+            E::NotBound => Ok(PortError::NotBound),
+            _ => Err(())
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum RestartError {
+    UnsupportedOption { field: String },
+    PortAlloc,
+    BadProxyHost,
+    BadProxyPort,
+    ProxyNotFound,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum ProfileError {
+    NotFound,
+    AlreadyExists,
+    AlreadyLoaded,
+    NotLoaded,
+    BadPassword,
+    LoadFailed,
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum AddFriendError {
     TooLong,
@@ -817,3 +1796,440 @@ impl TryFrom<rstox::core::errors::ConferenceTitleError> for ConferenceTitleError
         }
     }
 }
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupNewError {
+    TooLong,
+    Empty,
+    Init,
+    State,
+    Announce,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupJoinError {
+    Init,
+    BadChatId,
+    BadInvite,
+    FriendNotFound,
+    Empty,
+    TooLong,
+    Password,
+    Core,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupInviteError {
+    GroupNotFound,
+    FriendNotFound,
+    FailSend,
+    Disconnected,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupLeaveError {
+    GroupNotFound,
+    TooLong,
+    FailSend,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupQueryError {
+    GroupNotFound,
+    PeerNotFound,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupTopicError {
+    GroupNotFound,
+    TooLong,
+    Permissions,
+    FailCreate,
+    FailSend,
+    Disconnected,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupSendError {
+    GroupNotFound,
+    PeerNotFound,
+    TooLong,
+    Empty,
+    Permissions,
+    FailSend,
+    Disconnected,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupModerationError {
+    GroupNotFound,
+    PeerNotFound,
+    Permissions,
+    Assignment,
+    FailAction,
+    SelfAction,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum GroupFounderError {
+    GroupNotFound,
+    Permissions,
+    TooLong,
+    FailSet,
+    FailSend,
+    Disconnected,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum CallError {
+    Malloc,
+    Sync,
+    FriendNotFound,
+    FriendNotConnected,
+    FriendAlreadyInCall,
+    InvalidBitRate,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum AnswerError {
+    Sync,
+    CodecInitialization,
+    FriendNotFound,
+    FriendNotCalling,
+    InvalidBitRate,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum CallControlError {
+    Sync,
+    FriendNotFound,
+    FriendNotInCall,
+    InvalidTransition,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum BitRateSetError {
+    Sync,
+    InvalidBitRate,
+    FriendNotFound,
+    FriendNotInCall,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum SendFrameError {
+    Null,
+    FriendNotFound,
+    FriendNotInCall,
+    Sync,
+    Invalid,
+    PayloadTypeDisabled,
+    RtpFailed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_request_is_flat() {
+        let json = r#"{"profile":"work","request":"SetName","name":"Alice"}"#;
+
+        let request: ProfileRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.profile.as_deref(), Some("work"));
+        match request.request {
+            Request::SetName { ref name } => assert_eq!(name, "Alice"),
+            ref request => panic!("unexpected request {:?}", request),
+        }
+        assert_eq!(serde_json::to_string(&request).unwrap(), json);
+
+        let request: ProfileRequest = serde_json::from_str(r#"{"request":"Info"}"#).unwrap();
+        assert_eq!(request.profile, None);
+    }
+
+    #[test]
+    fn profile_message_is_flat() {
+        let response = ProfileMessage {
+            profile: Some("work".to_owned()),
+            message: Message::Response(Response::Ok),
+        };
+        let json = serde_json::to_string(&response).unwrap();
+        assert_eq!(json, r#"{"profile":"work","response":"Ok"}"#);
+        let response: ProfileMessage = serde_json::from_str(&json).unwrap();
+        assert!(matches!(response.message, Message::Response(Response::Ok)));
+
+        let event = ProfileMessage {
+            profile: None,
+            message: Message::Event(Event::FriendName { friend: 1, name: "Bob".to_owned() }),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(json, r#"{"profile":null,"event":"FriendName","friend":1,"name":"Bob"}"#);
+        let event: ProfileMessage = serde_json::from_str(&json).unwrap();
+        match event.message {
+            Message::Event(Event::FriendName { friend: 1, ref name }) => assert_eq!(name, "Bob"),
+            ref message => panic!("unexpected message {:?}", message),
+        }
+    }
+
+    fn peer(number: u32, public_key: &str) -> PeerInfo {
+        PeerInfo {
+            number,
+            public_key: public_key.to_owned(),
+            name: String::new(),
+            online: true,
+        }
+    }
+
+    #[test]
+    fn peer_list_diff_matches_by_public_key() {
+        let old = [peer(0, "A"), peer(1, "B")];
+        let new = [peer(0, "B"), peer(1, "C")];
+
+        let events = Event::from_peer_list_diff(7, &old, &new);
+
+        assert_eq!(events.len(), 2);
+        match events[0] {
+            Event::ConferencePeerLeft { conference: 7, ref peer } =>
+                assert_eq!(peer.public_key, "A"),
+            ref e => panic!("unexpected event {:?}", e),
+        }
+        match events[1] {
+            Event::ConferencePeerJoined { conference: 7, ref peer } =>
+                assert_eq!(peer.public_key, "C"),
+            ref e => panic!("unexpected event {:?}", e),
+        }
+    }
+
+    #[test]
+    fn peer_list_diff_ignores_renumbering() {
+        let old = [peer(0, "A"), peer(1, "B")];
+        let new = [peer(1, "A"), peer(0, "B")];
+
+        assert!(Event::from_peer_list_diff(0, &old, &new).is_empty());
+    }
+
+    #[test]
+    fn peer_list_diff_from_empty() {
+        let new = [peer(0, "A"), peer(1, "B")];
+
+        let events = Event::from_peer_list_diff(0, &[], &new);
+
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| matches!(*e, Event::ConferencePeerJoined { .. })));
+    }
+
+    #[test]
+    fn token_config_allows_granted_scopes() {
+        let config = TokenConfig { token: "t".to_owned(), scopes: vec![Scope::Read] };
+
+        assert!(config.allows(&Request::Info));
+        assert!(!config.allows(&Request::GetSecretKey));
+    }
+
+    #[test]
+    fn token_config_matches_exact_token() {
+        let config = TokenConfig { token: "secret".to_owned(), scopes: Vec::new() };
+
+        assert!(config.matches("secret"));
+        assert!(!config.matches("secreT"));
+        assert!(!config.matches("secret\0"));
+        assert!(!config.matches("secre"));
+        assert!(!config.matches(""));
+    }
+
+    #[test]
+    fn authenticate_needs_no_scope() {
+        let config = TokenConfig { token: "t".to_owned(), scopes: Vec::new() };
+        let request = Request::Authenticate { token: "t".to_owned() };
+
+        assert_eq!(request.scope(), None);
+        assert!(!request.requires_authentication());
+        assert!(Request::Info.requires_authentication());
+        assert!(config.allows(&request));
+    }
+
+    #[test]
+    fn default_options_are_supported() {
+        let options = ToxOptions { udp_enabled: false, ..ToxOptions::default() };
+
+        assert_eq!(options.unsupported_option(), None);
+    }
+
+    #[test]
+    fn unsupported_options_are_named() {
+        let default = ToxOptions::default;
+        let cases = [
+            ("ipv6_enabled", ToxOptions { ipv6_enabled: false, ..default() }),
+            ("local_discovery_enabled", ToxOptions { local_discovery_enabled: false, ..default() }),
+            ("hole_punching_enabled", ToxOptions { hole_punching_enabled: false, ..default() }),
+            ("start_port", ToxOptions { start_port: 40000, ..default() }),
+            ("end_port", ToxOptions { end_port: 40000, ..default() }),
+            ("tcp_port", ToxOptions { tcp_port: 443, ..default() }),
+        ];
+
+        for &(field, ref options) in &cases {
+            assert_eq!(options.unsupported_option(), Some(field));
+        }
+    }
+
+    #[test]
+    fn friend_without_newer_fields() {
+        let json = r#"{
+            "number": 0,
+            "public_key": "A",
+            "name": "",
+            "status": "None",
+            "status_message": "",
+            "last_online": 0
+        }"#;
+
+        let friend: Friend = serde_json::from_str(json).unwrap();
+
+        assert!(matches!(friend.connection_status, ConnectionStatus::None));
+        assert!(!friend.is_typing);
+        assert!(!friend.blocked);
+    }
+
+    #[test]
+    fn partial_friend_metadata() {
+        let json = r#"{"request": "SetFriendMetadata", "public_key": "A", "metadata": {"alias": "Bob"}}"#;
+
+        match serde_json::from_str(json).unwrap() {
+            Request::SetFriendMetadata { metadata, .. } => {
+                assert_eq!(metadata.alias.as_deref(), Some("Bob"));
+                assert!(metadata.notes.is_empty());
+                assert!(metadata.tags.is_empty());
+            },
+            request => panic!("unexpected request {:?}", request),
+        }
+    }
+
+    #[test]
+    fn pcm_is_little_endian_i16() {
+        let pcm = pcm_from_samples(&[1, -2, 0x1234]);
+
+        assert_eq!(pcm, [0x01, 0x00, 0xfe, 0xff, 0x34, 0x12]);
+        assert_eq!(pcm_to_samples(&pcm), Some(vec![1, -2, 0x1234]));
+        assert_eq!(pcm_to_samples(&pcm[.. 5]), None);
+    }
+
+    #[test]
+    fn pcm_sample_count_per_channel() {
+        assert_eq!(pcm_sample_count(&[0; 960 * 2 * 2], 2), Some(960));
+        assert_eq!(pcm_sample_count(&[0; 960 * 2], 1), Some(960));
+        assert_eq!(pcm_sample_count(&[0; 6], 2), None);
+        assert_eq!(pcm_sample_count(&[0; 4], 0), None);
+    }
+
+    const BLOCKED_KEY: &str = "0a1b2c3d4e5f60718293a4b5c6d7e8f9000102030405060708090a0b0c0d0e0f";
+
+    const BLOCKED_TOX_ID: &str =
+        "0A1B2C3D4E5F60718293A4B5C6D7E8F9000102030405060708090A0B0C0D0E0FDEADBEEF6042";
+
+    fn blocked() -> Vec<String> {
+        vec![BLOCKED_KEY.to_owned()]
+    }
+
+    #[test]
+    fn normalize_public_key_accepts_public_keys_and_tox_ids() {
+        let public_key = BLOCKED_KEY.to_ascii_uppercase();
+
+        assert_eq!(normalize_public_key(BLOCKED_KEY), Some(public_key.clone()));
+        assert_eq!(normalize_public_key(BLOCKED_TOX_ID), Some(public_key.clone()));
+        assert_eq!(normalize_public_key(&BLOCKED_TOX_ID.to_ascii_lowercase()), Some(public_key));
+    }
+
+    #[test]
+    fn normalize_public_key_rejects_bad_keys() {
+        let bad_checksum = format!("{}0000", &BLOCKED_TOX_ID[.. 72]);
+        let too_long = format!("{}00", BLOCKED_KEY);
+        let not_hex = format!("{}g", &BLOCKED_KEY[.. 63]);
+
+        assert_eq!(normalize_public_key(&bad_checksum), None);
+        assert_eq!(normalize_public_key(&too_long), None);
+        assert_eq!(normalize_public_key(&BLOCKED_KEY[.. 62]), None);
+        assert_eq!(normalize_public_key(&not_hex), None);
+        assert_eq!(normalize_public_key(""), None);
+    }
+
+    #[test]
+    fn is_blocked_friend_request_ignores_hex_case() {
+        let mut public_key = [0; 32];
+        for (i, b) in public_key.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&BLOCKED_KEY[2 * i .. 2 * i + 2], 16).unwrap();
+        }
+
+        let event = Event::FriendRequest { public_key, message: String::new() };
+
+        assert!(event.is_blocked(&blocked(), |_| None));
+        assert!(!event.is_blocked(&[], |_| None));
+    }
+
+    #[test]
+    fn is_blocked_friend_message_uses_resolver() {
+        let event = Event::FriendMessage {
+            friend: 3,
+            kind: MessageType::Normal,
+            message: String::new(),
+        };
+
+        assert!(event.is_blocked(&blocked(), |friend| match friend {
+            3 => Some(BLOCKED_KEY.to_ascii_uppercase()),
+            _ => None,
+        }));
+        assert!(!event.is_blocked(&blocked(), |_| None));
+    }
+
+    #[test]
+    fn is_blocked_matches_blocked_tox_id() {
+        let event = Event::FriendMessage {
+            friend: 3,
+            kind: MessageType::Normal,
+            message: String::new(),
+        };
+        let blocked = [BLOCKED_TOX_ID.to_owned()];
+
+        assert!(event.is_blocked(&blocked, |_| Some(BLOCKED_KEY.to_owned())));
+    }
+
+    #[test]
+    fn is_blocked_friend_invites_files_and_calls() {
+        let events = [
+            Event::FileReceipt {
+                friend: 3,
+                file_number: 0,
+                kind: FileKind::Data,
+                file_size: 0,
+                file_name: String::new(),
+            },
+            Event::ConferenceInvite { friend: 3, kind: ConferenceType::Text, cookie: Vec::new() },
+            Event::GroupInvite { friend: 3, invite_data: Vec::new(), group_name: String::new() },
+            Event::CallIncoming { friend: 3, audio_enabled: true, video_enabled: false },
+        ];
+
+        for event in &events {
+            assert!(event.is_blocked(&blocked(), |_| Some(BLOCKED_KEY.to_owned())), "{:?}", event);
+            assert!(!event.is_blocked(&[], |_| Some(BLOCKED_KEY.to_owned())), "{:?}", event);
+        }
+    }
+
+    #[test]
+    fn is_blocked_conference_message_without_key() {
+        let event = |public_key| Event::ConferenceMessage {
+            conference: 0,
+            peer: 1,
+            public_key,
+            kind: MessageType::Normal,
+            message: String::new(),
+        };
+
+        assert!(event(Some(BLOCKED_KEY.to_ascii_uppercase())).is_blocked(&blocked(), |_| None));
+        assert!(!event(None).is_blocked(&blocked(), |_| None));
+    }
+
+    #[test]
+    fn is_blocked_ignores_other_events() {
+        let event = Event::FriendName { friend: 3, name: String::new() };
+
+        assert!(!event.is_blocked(&blocked(), |_| Some(BLOCKED_KEY.to_owned())));
+    }
+}
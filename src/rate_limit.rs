@@ -0,0 +1,371 @@
+use serde::{Serialize, Deserialize};
+
+use std::cmp;
+use std::collections::HashMap;
+
+use crate::{Request, Response, Scope};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub per_second: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RateLimitConfig {
+    #[serde(default)]
+    pub scopes: HashMap<Scope, RateLimit>,
+    #[serde(default)]
+    pub per_friend: Option<RateLimit>,
+    /// Limit on `Authenticate` attempts, which need no scope. Limited by
+    /// default to slow down token guessing; `null` turns it off.
+    #[serde(default = "default_authenticate_limit")]
+    pub authenticate: Option<RateLimit>,
+}
+
+fn default_authenticate_limit() -> Option<RateLimit> {
+    Some(RateLimit { burst: 5, per_second: 1 })
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> RateLimitConfig {
+        RateLimitConfig {
+            scopes: HashMap::new(),
+            per_friend: None,
+            authenticate: default_authenticate_limit(),
+        }
+    }
+}
+
+/// A limit that could never let a request through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitError {
+    ZeroBurst,
+    ZeroRate,
+}
+
+impl RateLimit {
+    pub fn validate(&self) -> Result<(), RateLimitError> {
+        if self.burst == 0 {
+            Err(RateLimitError::ZeroBurst)
+        } else if self.per_second == 0 {
+            Err(RateLimitError::ZeroRate)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimited {
+    pub retry_after_ms: u64,
+}
+
+impl From<RateLimited> for Response {
+    fn from(error: RateLimited) -> Response {
+        Response::RateLimited { retry_after_ms: error.retry_after_ms }
+    }
+}
+
+/// Token bucket counting in thousandths of a token, so that refills
+/// of less than a whole token per millisecond are not lost.
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: u64,
+    updated_ms: u64,
+}
+
+const TOKEN: u64 = 1000;
+
+/// Friend buckets kept before full ones are dropped. A full bucket is the
+/// same as a new one, so dropping it forgets nothing.
+const MAX_FRIEND_BUCKETS: usize = 1024;
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now_ms: u64) -> TokenBucket {
+        TokenBucket {
+            limit,
+            tokens: u64::from(limit.burst) * TOKEN,
+            updated_ms: now_ms,
+        }
+    }
+
+    fn refill(&mut self, now_ms: u64) {
+        let elapsed = now_ms.saturating_sub(self.updated_ms);
+        let refilled = elapsed.saturating_mul(u64::from(self.limit.per_second));
+
+        self.tokens = cmp::min(self.tokens.saturating_add(refilled), self.capacity());
+        self.updated_ms = cmp::max(self.updated_ms, now_ms);
+    }
+
+    fn capacity(&self) -> u64 {
+        u64::from(self.limit.burst) * TOKEN
+    }
+
+    fn full_after_ms(&self) -> u64 {
+        let missing = self.capacity() - self.tokens;
+
+        missing.div_ceil(u64::from(self.limit.per_second))
+    }
+
+    fn retry_after_ms(&self) -> Option<u64> {
+        if self.tokens >= TOKEN {
+            return None
+        }
+
+        let missing = TOKEN - self.tokens;
+        let per_ms = u64::from(self.limit.per_second);
+
+        Some(missing.div_ceil(per_ms))
+    }
+
+    fn take(&mut self) {
+        self.tokens -= TOKEN;
+    }
+}
+
+/// Per-connection limiter. Requests are limited by their `Scope` and, for
+/// message and file requests, by the friend they are addressed to.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    scopes: HashMap<Scope, TokenBucket>,
+    friends: HashMap<u32, TokenBucket>,
+    authenticate: Option<TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Fails if any configured limit has a zero burst or rate.
+    pub fn new(config: RateLimitConfig) -> Result<RateLimiter, RateLimitError> {
+        let limits = config.scopes.values()
+            .chain(config.per_friend.iter())
+            .chain(config.authenticate.iter());
+        for limit in limits {
+            limit.validate()?;
+        }
+
+        Ok(RateLimiter {
+            config,
+            scopes: HashMap::new(),
+            friends: HashMap::new(),
+            authenticate: None,
+        })
+    }
+
+    /// Makes room for a bucket for `friend`, failing if every tracked
+    /// friend is still refilling.
+    fn reserve_friend(&mut self, friend: u32, now_ms: u64) -> Result<(), RateLimited> {
+        if self.friends.len() < MAX_FRIEND_BUCKETS || self.friends.contains_key(&friend) {
+            return Ok(())
+        }
+
+        self.friends.retain(|_, bucket| {
+            bucket.refill(now_ms);
+            bucket.tokens < bucket.capacity()
+        });
+
+        match self.friends.values().map(TokenBucket::full_after_ms).min() {
+            Some(retry_after_ms) if self.friends.len() >= MAX_FRIEND_BUCKETS =>
+                Err(RateLimited { retry_after_ms }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Accounts for `request` at `now_ms` milliseconds on a monotonic
+    /// clock, returning `RateLimited` if it must be rejected.
+    pub fn check(&mut self, request: &Request, now_ms: u64) -> Result<(), RateLimited> {
+        let scope = request.scope();
+        let friend = match scope {
+            Some(Scope::Message) | Some(Scope::File) => request.friend(),
+            _ => None,
+        };
+
+        if let (Some(friend), Some(_)) = (friend, self.config.per_friend) {
+            self.reserve_friend(friend, now_ms)?;
+        }
+
+        let scope_bucket = match scope {
+            Some(scope) => match self.config.scopes.get(&scope) {
+                Some(&limit) => Some(self.scopes.entry(scope)
+                    .or_insert_with(|| TokenBucket::new(limit, now_ms))),
+                None => None,
+            },
+            None => match self.config.authenticate {
+                Some(limit) => Some(self.authenticate
+                    .get_or_insert_with(|| TokenBucket::new(limit, now_ms))),
+                None => None,
+            },
+        };
+        let friend_bucket = match (friend, self.config.per_friend) {
+            (Some(friend), Some(limit)) => Some(self.friends.entry(friend)
+                .or_insert_with(|| TokenBucket::new(limit, now_ms))),
+            _ => None,
+        };
+
+        let mut buckets: Vec<&mut TokenBucket> = scope_bucket.into_iter()
+            .chain(friend_bucket)
+            .collect();
+
+        for bucket in buckets.iter_mut() {
+            bucket.refill(now_ms);
+        }
+
+        let retry_after_ms = buckets.iter()
+            .filter_map(|bucket| bucket.retry_after_ms())
+            .max();
+        if let Some(retry_after_ms) = retry_after_ms {
+            return Err(RateLimited { retry_after_ms })
+        }
+
+        for bucket in buckets.iter_mut() {
+            bucket.take();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MessageType;
+
+    fn limit(burst: u32, per_second: u32) -> RateLimit {
+        RateLimit { burst, per_second }
+    }
+
+    fn message(friend: u32) -> Request {
+        Request::SendFriendMessage { friend, kind: MessageType::Normal, message: "hi".to_owned() }
+    }
+
+    fn limiter(scopes: &[(Scope, RateLimit)], per_friend: Option<RateLimit>) -> RateLimiter {
+        RateLimiter::new(RateLimitConfig {
+            scopes: scopes.iter().cloned().collect(),
+            per_friend,
+            authenticate: None,
+        }).unwrap()
+    }
+
+    #[test]
+    fn zero_limits_are_rejected() {
+        let config = |limit| RateLimitConfig {
+            scopes: vec![(Scope::Read, limit)].into_iter().collect(),
+            ..RateLimitConfig::default()
+        };
+
+        assert_eq!(RateLimiter::new(config(limit(0, 1))).err(), Some(RateLimitError::ZeroBurst));
+        assert_eq!(RateLimiter::new(config(limit(1, 0))).err(), Some(RateLimitError::ZeroRate));
+        let per_friend = RateLimitConfig { per_friend: Some(limit(1, 0)), ..RateLimitConfig::default() };
+        assert_eq!(RateLimiter::new(per_friend).err(), Some(RateLimitError::ZeroRate));
+        let authenticate = RateLimitConfig { authenticate: Some(limit(0, 1)), ..RateLimitConfig::default() };
+        assert_eq!(RateLimiter::new(authenticate).err(), Some(RateLimitError::ZeroBurst));
+    }
+
+    #[test]
+    fn burst_then_limited() {
+        let mut limiter = limiter(&[(Scope::Read, limit(3, 1))], None);
+
+        for _ in 0 .. 3 {
+            assert_eq!(limiter.check(&Request::Info, 0), Ok(()));
+        }
+        assert_eq!(limiter.check(&Request::Info, 0), Err(RateLimited { retry_after_ms: 1000 }));
+    }
+
+    #[test]
+    fn refill_is_gradual_and_capped() {
+        let mut limiter = limiter(&[(Scope::Read, limit(2, 4))], None);
+
+        assert_eq!(limiter.check(&Request::Info, 0), Ok(()));
+        assert_eq!(limiter.check(&Request::Info, 0), Ok(()));
+
+        // Four tokens per second is one every 250ms.
+        assert_eq!(limiter.check(&Request::Info, 100), Err(RateLimited { retry_after_ms: 150 }));
+        assert_eq!(limiter.check(&Request::Info, 250), Ok(()));
+        assert_eq!(limiter.check(&Request::Info, 250), Err(RateLimited { retry_after_ms: 250 }));
+
+        // A long pause refills no more than the burst.
+        for _ in 0 .. 2 {
+            assert_eq!(limiter.check(&Request::Info, 60_000), Ok(()));
+        }
+        assert!(limiter.check(&Request::Info, 60_000).is_err());
+    }
+
+    #[test]
+    fn unconfigured_scopes_are_unlimited() {
+        let mut limiter = limiter(&[(Scope::Message, limit(1, 1))], None);
+
+        for _ in 0 .. 10 {
+            assert_eq!(limiter.check(&Request::Info, 0), Ok(()));
+        }
+    }
+
+    #[test]
+    fn authenticate_is_limited_by_default() {
+        let mut limiter = RateLimiter::default();
+        let authenticate = Request::Authenticate { token: "guess".to_owned() };
+
+        for _ in 0 .. 5 {
+            assert_eq!(limiter.check(&authenticate, 0), Ok(()));
+        }
+        assert_eq!(limiter.check(&authenticate, 0), Err(RateLimited { retry_after_ms: 1000 }));
+        assert_eq!(limiter.check(&authenticate, 1000), Ok(()));
+
+        // Other requests don't share the authentication bucket.
+        assert_eq!(limiter.check(&Request::Info, 1000), Ok(()));
+    }
+
+    #[test]
+    fn authenticate_limit_can_be_turned_off() {
+        let config: RateLimitConfig = serde_json::from_str(r#"{"authenticate": null}"#).unwrap();
+        let mut limiter = RateLimiter::new(config).unwrap();
+
+        for _ in 0 .. 100 {
+            assert_eq!(limiter.check(&Request::Authenticate { token: String::new() }, 0), Ok(()));
+        }
+    }
+
+    #[test]
+    fn full_friend_buckets_are_dropped() {
+        let mut limiter = limiter(&[], Some(limit(1, 10)));
+
+        for friend in 0 .. MAX_FRIEND_BUCKETS as u32 {
+            assert_eq!(limiter.check(&message(friend), 0), Ok(()));
+        }
+
+        // Every bucket is still refilling, so a new friend has to wait.
+        let friend = MAX_FRIEND_BUCKETS as u32;
+        assert_eq!(limiter.check(&message(friend), 0), Err(RateLimited { retry_after_ms: 100 }));
+        // Known friends aren't affected.
+        assert_eq!(limiter.check(&message(0), 100), Ok(()));
+
+        assert_eq!(limiter.check(&message(friend), 100), Ok(()));
+        assert!(limiter.friends.len() <= 2);
+    }
+
+    #[test]
+    fn per_friend_limits_each_friend() {
+        let mut limiter = limiter(&[], Some(limit(1, 1)));
+
+        assert_eq!(limiter.check(&message(1), 0), Ok(()));
+        assert!(limiter.check(&message(1), 0).is_err());
+        assert_eq!(limiter.check(&message(2), 0), Ok(()));
+
+        // Read requests addressed to a friend don't use the friend bucket.
+        assert_eq!(limiter.check(&Request::GetFriendName { friend: 1 }, 0), Ok(()));
+    }
+
+    #[test]
+    fn scope_and_friend_limits_combine() {
+        let mut limiter = limiter(&[(Scope::Message, limit(2, 1))], Some(limit(1, 2)));
+
+        assert_eq!(limiter.check(&message(1), 0), Ok(()));
+        // Friend 1 is out of tokens; the rejected request takes no scope token.
+        assert_eq!(limiter.check(&message(1), 0), Err(RateLimited { retry_after_ms: 500 }));
+        assert_eq!(limiter.check(&message(2), 0), Ok(()));
+        // The scope bucket is empty now, so friend 3 waits for it.
+        assert_eq!(limiter.check(&message(3), 0), Err(RateLimited { retry_after_ms: 1000 }));
+        // The longest wait of the two buckets is reported.
+        assert_eq!(limiter.check(&message(1), 0), Err(RateLimited { retry_after_ms: 1000 }));
+    }
+}
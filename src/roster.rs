@@ -0,0 +1,197 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{normalize_public_key, AddFriendError, Friend};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RosterResult {
+    pub public_key: String,
+    pub outcome: RosterOutcome,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum RosterOutcome {
+    Unchanged,
+    Added { friend: u32 },
+    Removed,
+    AddFailed { error: AddFriendError },
+    RemoveFailed,
+    InvalidKey,
+    /// Not added or removed because the roster held an invalid key.
+    NotApplied,
+}
+
+/// Changes needed to turn the current friend list into the desired one.
+/// If any desired key is invalid the whole plan is rejected: `add` and
+/// `remove` stay empty and their entries go to `not_added` and
+/// `not_removed` instead.
+#[derive(Clone, Debug, Default)]
+pub struct RosterPlan {
+    pub add: Vec<String>,
+    pub remove: Vec<Friend>,
+    pub keep: Vec<Friend>,
+    pub invalid: Vec<String>,
+    pub not_added: Vec<String>,
+    pub not_removed: Vec<Friend>,
+}
+
+impl RosterPlan {
+    /// `desired` may contain public keys or Tox IDs; duplicates are ignored.
+    pub fn new(current: &[Friend], desired: &[String]) -> RosterPlan {
+        let mut plan = RosterPlan::default();
+        let mut wanted: Vec<String> = Vec::new();
+
+        for key in desired {
+            match normalize_public_key(key) {
+                Some(key) => if !wanted.contains(&key) {
+                    wanted.push(key)
+                },
+                None => plan.invalid.push(key.clone()),
+            }
+        }
+
+        for friend in current {
+            let is_wanted = normalize_public_key(&friend.public_key)
+                .is_some_and(|key| wanted.contains(&key));
+
+            if is_wanted {
+                plan.keep.push(friend.clone());
+            } else {
+                plan.remove.push(friend.clone());
+            }
+        }
+
+        plan.add = wanted.into_iter()
+            .filter(|key| !current.iter()
+                .any(|friend| normalize_public_key(&friend.public_key).as_ref() == Some(key)))
+            .collect();
+
+        if !plan.invalid.is_empty() {
+            plan.not_added = std::mem::take(&mut plan.add);
+            plan.not_removed = std::mem::take(&mut plan.remove);
+        }
+
+        plan
+    }
+
+    /// Results for the parts of the plan that need no action.
+    pub fn unchanged_results(&self) -> Vec<RosterResult> {
+        let kept = self.keep.iter()
+            .map(|friend| RosterResult {
+                public_key: friend.public_key.clone(),
+                outcome: RosterOutcome::Unchanged,
+            });
+        let not_added = self.not_added.iter()
+            .map(|key| RosterResult {
+                public_key: key.clone(),
+                outcome: RosterOutcome::NotApplied,
+            });
+        let not_removed = self.not_removed.iter()
+            .map(|friend| RosterResult {
+                public_key: friend.public_key.clone(),
+                outcome: RosterOutcome::NotApplied,
+            });
+        let invalid = self.invalid.iter()
+            .map(|key| RosterResult {
+                public_key: key.clone(),
+                outcome: RosterOutcome::InvalidKey,
+            });
+
+        kept.chain(not_added).chain(not_removed).chain(invalid).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ConnectionStatus, LocalMetadata, UserStatus};
+
+    const KEY_A: &str = "CA978112CA1BBDCAFAC231B39A23DC4DA786EFF8147C4E72B9807785AFEE48BB";
+    const KEY_B: &str = "3E23E8160039594A33894F6564E1B1348BBD7A0088D42C4ACB73EEAED59C009D";
+    const KEY_C: &str = "2E7D2C03A9507AE265ECF5B5356885A53393A2029D241394997265A1A25AEFC6";
+    const TOX_ID_C: &str =
+        "2E7D2C03A9507AE265ECF5B5356885A53393A2029D241394997265A1A25AEFC6DEADBEEF3F74";
+
+    fn friend(number: u32, public_key: &str) -> Friend {
+        Friend {
+            number,
+            public_key: public_key.to_owned(),
+            name: String::new(),
+            status: UserStatus::None,
+            status_message: String::new(),
+            last_online: 0,
+            connection_status: ConnectionStatus::None,
+            is_typing: false,
+            metadata: LocalMetadata::default(),
+            blocked: false,
+        }
+    }
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|&key| key.to_owned()).collect()
+    }
+
+    fn numbers(friends: &[Friend]) -> Vec<u32> {
+        friends.iter().map(|friend| friend.number).collect()
+    }
+
+    #[test]
+    fn plan_adds_keeps_and_removes() {
+        let current = [friend(0, KEY_A), friend(1, KEY_B)];
+
+        let plan = RosterPlan::new(&current, &keys(&[KEY_A, KEY_C]));
+
+        assert_eq!(plan.add, keys(&[KEY_C]));
+        assert_eq!(numbers(&plan.keep), [0]);
+        assert_eq!(numbers(&plan.remove), [1]);
+        assert!(plan.invalid.is_empty());
+        assert!(plan.not_added.is_empty());
+        assert!(plan.not_removed.is_empty());
+    }
+
+    #[test]
+    fn plan_ignores_duplicates_and_key_forms() {
+        let current = [friend(0, KEY_C)];
+        let desired = keys(&[KEY_A, &KEY_A.to_ascii_lowercase(), TOX_ID_C, KEY_C]);
+
+        let plan = RosterPlan::new(&current, &desired);
+
+        assert_eq!(plan.add, keys(&[KEY_A]));
+        assert_eq!(numbers(&plan.keep), [0]);
+        assert!(plan.remove.is_empty());
+    }
+
+    #[test]
+    fn plan_with_invalid_key_changes_nothing() {
+        let current = [friend(0, KEY_A), friend(1, KEY_B)];
+
+        let plan = RosterPlan::new(&current, &keys(&[KEY_A, KEY_C, "nope"]));
+
+        assert!(plan.add.is_empty());
+        assert!(plan.remove.is_empty());
+        assert_eq!(numbers(&plan.keep), [0]);
+        assert_eq!(plan.not_added, keys(&[KEY_C]));
+        assert_eq!(numbers(&plan.not_removed), [1]);
+        assert_eq!(plan.invalid, keys(&["nope"]));
+
+        let results = plan.unchanged_results();
+        let outcome = |key: &str| results.iter()
+            .find(|result| result.public_key == key)
+            .map(|result| result.outcome);
+
+        assert_eq!(results.len(), 4);
+        assert!(matches!(outcome(KEY_A), Some(RosterOutcome::Unchanged)));
+        assert!(matches!(outcome(KEY_B), Some(RosterOutcome::NotApplied)));
+        assert!(matches!(outcome(KEY_C), Some(RosterOutcome::NotApplied)));
+        assert!(matches!(outcome("nope"), Some(RosterOutcome::InvalidKey)));
+    }
+
+    #[test]
+    fn empty_plan_removes_everyone() {
+        let current = [friend(0, KEY_A)];
+
+        let plan = RosterPlan::new(&current, &[]);
+
+        assert!(plan.add.is_empty());
+        assert_eq!(numbers(&plan.remove), [0]);
+    }
+}
@@ -0,0 +1,423 @@
+use serde::{Serialize, Deserialize};
+
+use crate::{normalize_public_key, pcm_sample_count, Request, Response};
+
+pub const MAX_NAME_LENGTH: usize = 128;
+pub const MAX_STATUS_MESSAGE_LENGTH: usize = 1007;
+pub const MAX_FRIEND_REQUEST_LENGTH: usize = 1016;
+pub const MAX_MESSAGE_LENGTH: usize = 1372;
+pub const MAX_FILENAME_LENGTH: usize = 255;
+pub const MAX_FILE_CHUNK_LENGTH: usize = 1371;
+pub const MAX_CONFERENCE_TITLE_LENGTH: usize = 128;
+pub const MAX_GROUP_NAME_LENGTH: usize = 48;
+pub const MAX_GROUP_TOPIC_LENGTH: usize = 512;
+pub const MAX_GROUP_PASSWORD_LENGTH: usize = 32;
+pub const MAX_GROUP_PART_LENGTH: usize = 128;
+pub const MAX_AVATAR_SIZE: usize = 65536;
+/// Invites travel in a single lossless friend packet.
+pub const MAX_INVITE_LENGTH: usize = 1372;
+/// 120 ms at 48 kHz, the longest frame toxav accepts.
+pub const MAX_AUDIO_FRAME_SAMPLES: usize = 5760;
+pub const MAX_AUDIO_CHANNELS: u8 = 2;
+const NOSPAM_HEX_LENGTH: usize = 8;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    Empty,
+    TooLong { max: u32 },
+    Malformed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub error: ValidationError,
+}
+
+impl From<FieldError> for Response {
+    fn from(error: FieldError) -> Response {
+        Response::ValidationError {
+            field: error.field.to_owned(),
+            error: error.error,
+        }
+    }
+}
+
+fn max_len(field: &'static str, value: &[u8], max: usize) -> Result<(), FieldError> {
+    if value.len() > max {
+        Err(FieldError { field, error: ValidationError::TooLong { max: max as u32 } })
+    } else {
+        Ok(())
+    }
+}
+
+fn non_empty(field: &'static str, value: &[u8], max: usize) -> Result<(), FieldError> {
+    if value.is_empty() {
+        Err(FieldError { field, error: ValidationError::Empty })
+    } else {
+        max_len(field, value, max)
+    }
+}
+
+fn well_formed(field: &'static str, valid: bool) -> Result<(), FieldError> {
+    if valid {
+        Ok(())
+    } else {
+        Err(FieldError { field, error: ValidationError::Malformed })
+    }
+}
+
+fn valid_key(field: &'static str, value: &str) -> Result<(), FieldError> {
+    well_formed(field, normalize_public_key(value).is_some())
+}
+
+fn valid_nospam(field: &'static str, value: &str) -> Result<(), FieldError> {
+    well_formed(field, value.len() == NOSPAM_HEX_LENGTH
+        && value.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn valid_plane(field: &'static str, plane: &[u8], width: u16, height: u16) -> Result<(), FieldError> {
+    well_formed(field, plane.len() == usize::from(width) * usize::from(height))
+}
+
+fn join_group(self_name: &str, password: Option<&str>) -> Result<(), FieldError> {
+    non_empty("self_name", self_name.as_bytes(), MAX_NAME_LENGTH)?;
+    match password {
+        Some(password) => max_len("password", password.as_bytes(), MAX_GROUP_PASSWORD_LENGTH),
+        None => Ok(()),
+    }
+}
+
+impl Request {
+    /// Checks the request against Tox size limits and key formats before it
+    /// reaches toxcore.
+    pub fn validate(&self) -> Result<(), FieldError> {
+        match *self {
+            Request::SetInfo { ref nospam, ref name, ref status_message, .. } => {
+                valid_nospam("nospam", nospam)?;
+                max_len("name", name.as_bytes(), MAX_NAME_LENGTH)?;
+                max_len("status_message", status_message.as_bytes(), MAX_STATUS_MESSAGE_LENGTH)
+            },
+            Request::AddFriend { ref message, .. } =>
+                non_empty("message", message.as_bytes(), MAX_FRIEND_REQUEST_LENGTH),
+            Request::SendFriendMessage { ref message, .. }
+            | Request::SendConferenceMessage { ref message, .. }
+            | Request::SendGroupMessage { ref message, .. }
+            | Request::SendGroupPrivateMessage { ref message, .. } =>
+                non_empty("message", message.as_bytes(), MAX_MESSAGE_LENGTH),
+            Request::SetName { ref name } =>
+                max_len("name", name.as_bytes(), MAX_NAME_LENGTH),
+            Request::SetStatusMessage { ref message } =>
+                max_len("message", message.as_bytes(), MAX_STATUS_MESSAGE_LENGTH),
+            Request::SendFile { ref file_name, .. } =>
+                max_len("file_name", file_name.as_bytes(), MAX_FILENAME_LENGTH),
+            Request::SendFileChunk { ref data, .. } =>
+                max_len("data", data, MAX_FILE_CHUNK_LENGTH),
+            Request::SetConferenceTitle { ref title, .. } =>
+                max_len("title", title.as_bytes(), MAX_CONFERENCE_TITLE_LENGTH),
+            Request::NewGroup { ref name, ref self_name, .. } => {
+                non_empty("name", name.as_bytes(), MAX_GROUP_NAME_LENGTH)?;
+                non_empty("self_name", self_name.as_bytes(), MAX_NAME_LENGTH)
+            },
+            Request::JoinGroup { ref self_name, ref password, .. } =>
+                join_group(self_name, password.as_deref()),
+            Request::LeaveGroup { ref message, .. } =>
+                max_len("message", message.as_bytes(), MAX_GROUP_PART_LENGTH),
+            Request::SetGroupTopic { ref topic, .. } =>
+                max_len("topic", topic.as_bytes(), MAX_GROUP_TOPIC_LENGTH),
+            Request::SetGroupPassword { password: Some(ref password), .. } =>
+                max_len("password", password.as_bytes(), MAX_GROUP_PASSWORD_LENGTH),
+            Request::SetNospam { ref nospam } =>
+                valid_nospam("nospam", nospam),
+            Request::AcceptFriendRequest { ref public_key }
+            | Request::RejectFriendRequest { ref public_key }
+            | Request::FriendByPublicKey { ref public_key }
+            | Request::GetFriendMetadata { ref public_key }
+            | Request::SetFriendMetadata { ref public_key, .. }
+            | Request::Block { ref public_key }
+            | Request::Unblock { ref public_key } =>
+                valid_key("public_key", public_key),
+            Request::SetAvatar { ref data } =>
+                max_len("data", data, MAX_AVATAR_SIZE),
+            Request::JoinConference { ref cookie, .. } =>
+                non_empty("cookie", cookie, MAX_INVITE_LENGTH),
+            Request::AcceptGroupInvite { ref invite_data, ref self_name, ref password, .. } => {
+                non_empty("invite_data", invite_data, MAX_INVITE_LENGTH)?;
+                join_group(self_name, password.as_deref())
+            },
+            Request::SendAudioFrame { ref pcm, channels, .. } => {
+                well_formed("channels", (1 ..= MAX_AUDIO_CHANNELS).contains(&channels))?;
+                let frame_size = 2 * usize::from(channels);
+                non_empty("pcm", pcm, MAX_AUDIO_FRAME_SAMPLES * frame_size)?;
+                well_formed("pcm", pcm_sample_count(pcm, channels).is_some())
+            },
+            Request::SendVideoFrame { width, height, ref y, ref u, ref v, .. } => {
+                well_formed("width", width != 0)?;
+                well_formed("height", height != 0)?;
+                valid_plane("y", y, width, height)?;
+                valid_plane("u", u, width / 2, height / 2)?;
+                valid_plane("v", v, width / 2, height / 2)
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FileKind, GroupPrivacyState, LocalMetadata, MessageType, UserStatus};
+
+    fn too_long(field: &'static str, max: usize) -> Result<(), FieldError> {
+        Err(FieldError { field, error: ValidationError::TooLong { max: max as u32 } })
+    }
+
+    fn malformed(field: &'static str) -> Result<(), FieldError> {
+        Err(FieldError { field, error: ValidationError::Malformed })
+    }
+
+    /// Checks that `field` accepts exactly `max` bytes and rejects one more.
+    fn assert_limit<F>(field: &'static str, max: usize, request: F)
+        where F: Fn(String) -> Request
+    {
+        assert_eq!(request("a".repeat(max)).validate(), Ok(()), "{}", field);
+        assert_eq!(request("a".repeat(max + 1)).validate(), too_long(field, max), "{}", field);
+    }
+
+    fn assert_non_empty<F>(field: &'static str, request: F)
+        where F: Fn(String) -> Request
+    {
+        assert_eq!(
+            request(String::new()).validate(),
+            Err(FieldError { field, error: ValidationError::Empty }),
+            "{}",
+            field
+        );
+    }
+
+    fn set_info(name: String, status_message: String) -> Request {
+        Request::SetInfo {
+            nospam: "DEADBEEF".to_owned(),
+            name,
+            status: UserStatus::None,
+            status_message,
+            friends: None,
+        }
+    }
+
+    #[test]
+    fn friend_limits() {
+        assert_limit("name", MAX_NAME_LENGTH, |name| set_info(name, String::new()));
+        assert_limit("status_message", MAX_STATUS_MESSAGE_LENGTH,
+            |message| set_info(String::new(), message));
+        assert_limit("name", MAX_NAME_LENGTH, |name| Request::SetName { name });
+        assert_limit("message", MAX_STATUS_MESSAGE_LENGTH,
+            |message| Request::SetStatusMessage { message });
+        assert_limit("message", MAX_FRIEND_REQUEST_LENGTH,
+            |message| Request::AddFriend { tox_id: String::new(), message });
+    }
+
+    #[test]
+    fn message_limits() {
+        assert_limit("message", MAX_MESSAGE_LENGTH, |message| Request::SendFriendMessage {
+            friend: 0,
+            kind: MessageType::Normal,
+            message,
+        });
+        assert_limit("message", MAX_MESSAGE_LENGTH, |message| Request::SendConferenceMessage {
+            conference: 0,
+            kind: MessageType::Normal,
+            message,
+        });
+        assert_limit("message", MAX_MESSAGE_LENGTH, |message| Request::SendGroupMessage {
+            group: 0,
+            kind: MessageType::Normal,
+            message,
+        });
+        assert_limit("message", MAX_MESSAGE_LENGTH, |message| Request::SendGroupPrivateMessage {
+            group: 0,
+            peer: 0,
+            kind: MessageType::Action,
+            message,
+        });
+    }
+
+    #[test]
+    fn file_limits() {
+        assert_limit("file_name", MAX_FILENAME_LENGTH, |file_name| Request::SendFile {
+            friend: 0,
+            kind: FileKind::Data,
+            file_size: 0,
+            file_name,
+        });
+        assert_limit("data", MAX_FILE_CHUNK_LENGTH, |data| Request::SendFileChunk {
+            friend: 0,
+            file_number: 0,
+            position: 0,
+            data: data.into_bytes(),
+        });
+    }
+
+    #[test]
+    fn conference_and_group_limits() {
+        assert_limit("title", MAX_CONFERENCE_TITLE_LENGTH,
+            |title| Request::SetConferenceTitle { conference: 0, title });
+        assert_limit("name", MAX_GROUP_NAME_LENGTH, |name| Request::NewGroup {
+            privacy: GroupPrivacyState::Public,
+            name,
+            self_name: "me".to_owned(),
+        });
+        assert_limit("self_name", MAX_NAME_LENGTH, |self_name| Request::NewGroup {
+            privacy: GroupPrivacyState::Public,
+            name: "group".to_owned(),
+            self_name,
+        });
+        assert_limit("password", MAX_GROUP_PASSWORD_LENGTH, |password| Request::JoinGroup {
+            chat_id: String::new(),
+            self_name: "me".to_owned(),
+            password: Some(password),
+        });
+        assert_limit("password", MAX_GROUP_PASSWORD_LENGTH, |password| Request::AcceptGroupInvite {
+            friend: 0,
+            invite_data: vec![0; 35],
+            self_name: "me".to_owned(),
+            password: Some(password),
+        });
+        assert_limit("message", MAX_GROUP_PART_LENGTH,
+            |message| Request::LeaveGroup { group: 0, message });
+        assert_limit("topic", MAX_GROUP_TOPIC_LENGTH,
+            |topic| Request::SetGroupTopic { group: 0, topic });
+        assert_limit("password", MAX_GROUP_PASSWORD_LENGTH,
+            |password| Request::SetGroupPassword { group: 0, password: Some(password) });
+    }
+
+    #[test]
+    fn empty_is_reported_apart_from_too_long() {
+        assert_non_empty("message",
+            |message| Request::AddFriend { tox_id: String::new(), message });
+        assert_non_empty("message", |message| Request::SendFriendMessage {
+            friend: 0,
+            kind: MessageType::Normal,
+            message,
+        });
+        assert_non_empty("name", |name| Request::NewGroup {
+            privacy: GroupPrivacyState::Private,
+            name,
+            self_name: "me".to_owned(),
+        });
+        assert_non_empty("self_name", |self_name| Request::JoinGroup {
+            chat_id: String::new(),
+            self_name,
+            password: None,
+        });
+    }
+
+    #[test]
+    fn empty_is_allowed_where_tox_allows_it() {
+        assert_eq!(set_info(String::new(), String::new()).validate(), Ok(()));
+        assert_eq!(Request::SetName { name: String::new() }.validate(), Ok(()));
+        assert_eq!(Request::LeaveGroup { group: 0, message: String::new() }.validate(), Ok(()));
+        assert_eq!(Request::SetGroupPassword { group: 0, password: None }.validate(), Ok(()));
+    }
+
+    const PUBLIC_KEY: &str = "CA978112CA1BBDCAFAC231B39A23DC4DA786EFF8147C4E72B9807785AFEE48BB";
+    const TOX_ID: &str = "CA978112CA1BBDCAFAC231B39A23DC4DA786EFF8147C4E72B9807785AFEE48BBDEADBEEFEA29";
+
+    #[test]
+    fn public_keys_must_be_public_keys_or_tox_ids() {
+        let requests: [fn(String) -> Request; 7] = [
+            |public_key| Request::AcceptFriendRequest { public_key },
+            |public_key| Request::RejectFriendRequest { public_key },
+            |public_key| Request::FriendByPublicKey { public_key },
+            |public_key| Request::GetFriendMetadata { public_key },
+            |public_key| Request::SetFriendMetadata { public_key, metadata: LocalMetadata::default() },
+            |public_key| Request::Block { public_key },
+            |public_key| Request::Unblock { public_key },
+        ];
+
+        for request in &requests {
+            assert_eq!(request(PUBLIC_KEY.to_owned()).validate(), Ok(()));
+            assert_eq!(request(TOX_ID.to_ascii_lowercase()).validate(), Ok(()));
+            assert_eq!(request("A".to_owned()).validate(), malformed("public_key"));
+            assert_eq!(request(TOX_ID.replace("EA29", "0000")).validate(), malformed("public_key"));
+        }
+    }
+
+    #[test]
+    fn nospam_must_be_eight_hex_digits() {
+        let nospam = |nospam: &str| Request::SetNospam { nospam: nospam.to_owned() };
+        let info = Request::SetInfo {
+            nospam: "DEADBEE".to_owned(),
+            name: String::new(),
+            status: UserStatus::None,
+            status_message: String::new(),
+            friends: None,
+        };
+
+        assert_eq!(nospam("deadbeef").validate(), Ok(()));
+        assert_eq!(nospam("DEADBEE").validate(), malformed("nospam"));
+        assert_eq!(nospam("DEADBEEG").validate(), malformed("nospam"));
+        assert_eq!(info.validate(), malformed("nospam"));
+    }
+
+    #[test]
+    fn binary_limits() {
+        assert_limit("data", MAX_AVATAR_SIZE,
+            |data| Request::SetAvatar { data: data.into_bytes() });
+        assert_limit("cookie", MAX_INVITE_LENGTH,
+            |cookie| Request::JoinConference { friend: 0, cookie: cookie.into_bytes() });
+        assert_limit("invite_data", MAX_INVITE_LENGTH, |invite_data| Request::AcceptGroupInvite {
+            friend: 0,
+            invite_data: invite_data.into_bytes(),
+            self_name: "me".to_owned(),
+            password: None,
+        });
+    }
+
+    fn audio(pcm: Vec<u8>, channels: u8) -> Request {
+        Request::SendAudioFrame { friend: 0, pcm, channels, sample_rate: 48000 }
+    }
+
+    #[test]
+    fn audio_frames_hold_whole_samples() {
+        assert_eq!(audio(vec![0; 960 * 4], 2).validate(), Ok(()));
+        assert_eq!(audio(vec![0; MAX_AUDIO_FRAME_SAMPLES * 4], 2).validate(), Ok(()));
+        assert_eq!(
+            audio(vec![0; MAX_AUDIO_FRAME_SAMPLES * 4 + 4], 2).validate(),
+            too_long("pcm", MAX_AUDIO_FRAME_SAMPLES * 4)
+        );
+        assert_eq!(audio(vec![0; 6], 2).validate(), malformed("pcm"));
+        assert_eq!(audio(Vec::new(), 1).validate(),
+            Err(FieldError { field: "pcm", error: ValidationError::Empty }));
+        assert_eq!(audio(vec![0; 4], 0).validate(), malformed("channels"));
+        assert_eq!(audio(vec![0; 6], 3).validate(), malformed("channels"));
+    }
+
+    fn video(width: u16, height: u16, y: usize, uv: usize) -> Request {
+        Request::SendVideoFrame {
+            friend: 0,
+            width,
+            height,
+            y: vec![0; y],
+            u: vec![0; uv],
+            v: vec![0; uv],
+        }
+    }
+
+    #[test]
+    fn video_planes_match_dimensions() {
+        assert_eq!(video(4, 2, 8, 2).validate(), Ok(()));
+        assert_eq!(video(5, 3, 15, 2).validate(), Ok(()));
+        assert_eq!(video(4, 2, 9, 2).validate(), malformed("y"));
+        assert_eq!(video(4, 2, 8, 4).validate(), malformed("u"));
+        assert_eq!(video(0, 2, 0, 0).validate(), malformed("width"));
+        assert_eq!(video(4, 0, 0, 0).validate(), malformed("height"));
+    }
+
+    #[test]
+    fn limits_count_bytes() {
+        let name = "é".repeat(MAX_NAME_LENGTH / 2 + 1);
+
+        assert_eq!(Request::SetName { name }.validate(), too_long("name", MAX_NAME_LENGTH));
+    }
+}